@@ -0,0 +1,616 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+/// Arbitrary-precision unsigned integer.
+///
+/// Stored as base 2^32 limbs, least significant first, with no trailing zero limbs so that every
+/// value has exactly one representation (zero is the empty vec).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: vec![] }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Returns self * factor.
+    pub fn mul_small(&self, factor: u32) -> Self {
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        let mut carry = 0u64;
+        for &limb in &self.limbs {
+            let product = limb as u64 * factor as u64 + carry;
+            limbs.push(product as u32);
+            carry = product >> 32;
+        }
+        limbs.push(carry as u32);
+        Self::from_limbs(limbs)
+    }
+
+    /// Returns (self / divisor, self % divisor).
+    pub fn div_rem_small(&self, divisor: u32) -> (Self, u32) {
+        assert!(divisor != 0, "division by zero");
+
+        // Long division from the most significant limb down.
+        let mut limbs = vec![0; self.limbs.len()];
+        let mut remainder = 0u64;
+        for (index, &limb) in self.limbs.iter().enumerate().rev() {
+            let current = (remainder << 32) | limb as u64;
+            limbs[index] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        (Self::from_limbs(limbs), remainder as u32)
+    }
+
+    /// Returns self % divisor.
+    pub fn rem_small(&self, divisor: u32) -> u32 {
+        self.div_rem_small(divisor).1
+    }
+
+    /// Number of bits needed to represent the value, 0 for zero.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(&top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// Returns the value if it fits in a u64.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0] as u64),
+            2 => Some(self.limbs[0] as u64 | (self.limbs[1] as u64) << 32),
+            _ => None,
+        }
+    }
+
+    fn from_limbs(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    /// Returns self * 2^shift.
+    fn shl_bits(&self, shift: u64) -> Self {
+        let bit_shift = shift % 32;
+        let mut limbs = vec![0; (shift / 32) as usize];
+        let mut carry = 0u32;
+        for &limb in &self.limbs {
+            let shifted = (limb as u64) << bit_shift;
+            limbs.push(shifted as u32 | carry);
+            carry = (shifted >> 32) as u32;
+        }
+        limbs.push(carry);
+        Self::from_limbs(limbs)
+    }
+
+    /// Returns self / 2^shift, rounded down.
+    fn shr_bits(&self, shift: u64) -> Self {
+        let limb_shift = (shift / 32) as usize;
+        if limb_shift >= self.limbs.len() {
+            return Self::zero();
+        }
+
+        let bit_shift = shift % 32;
+        let high = &self.limbs[limb_shift..];
+        let limbs = (0..high.len())
+            .map(|index| {
+                let next = *high.get(index + 1).unwrap_or(&0) as u64;
+                ((high[index] as u64 | next << 32) >> bit_shift) as u32
+            })
+            .collect();
+        Self::from_limbs(limbs)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self::from_limbs(vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl<'a> Add<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let (longer, shorter) = if self.limbs.len() >= other.limbs.len() {
+            (&self.limbs, &other.limbs)
+        } else {
+            (&other.limbs, &self.limbs)
+        };
+
+        let mut limbs = Vec::with_capacity(longer.len() + 1);
+        let mut carry = 0u64;
+        for (index, &limb) in longer.iter().enumerate() {
+            let sum = limb as u64 + *shorter.get(index).unwrap_or(&0) as u64 + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: BigUint) -> BigUint {
+        &self + &other
+    }
+}
+
+impl<'a> Sub<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    /// Panics if other is bigger than self.
+    fn sub(self, other: &BigUint) -> BigUint {
+        assert!(*self >= *other, "subtraction underflowed");
+        BigUint::from_limbs(sub_limbs(&self.limbs, &other.limbs))
+    }
+}
+
+impl Sub for BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: BigUint) -> BigUint {
+        &self - &other
+    }
+}
+
+impl<'a> Mul<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        BigUint::from_limbs(mul_limbs(&self.limbs, &other.limbs))
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        &self * &other
+    }
+}
+
+/// Below this many limbs, schoolbook multiplication is faster than splitting further.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/*
+Karatsuba multiplication:
+Split each number in half at B = 2^(32 * m), so a = a1 * B + a0 and b = b1 * B + b0. Then
+a * b = z2 * B^2 + z1 * B + z0 where
+z0 = a0 * b0
+z2 = a1 * b1
+z1 = (a0 + a1) * (b0 + b1) - z0 - z2
+which is 3 half-size multiplications instead of 4.
+
+Time complexity: O(N^log2(3)) ~ O(N^1.585) for N limbs, compared to O(N^2) for schoolbook.
+*/
+/// Product of two little-endian limb slices, may have trailing zero limbs.
+fn mul_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    if a.len().min(b.len()) < KARATSUBA_THRESHOLD {
+        return schoolbook_mul_limbs(a, b);
+    }
+
+    let m = a.len().max(b.len()) / 2;
+    if a.len() <= m || b.len() <= m {
+        // Too lopsided to split both, split the longer one only: a * b = a1 * b * B + a0 * b.
+        let (longer, shorter) = if a.len() > b.len() { (a, b) } else { (b, a) };
+        let mut result = mul_limbs(&longer[..m], shorter);
+        add_limbs_at(&mut result, &mul_limbs(&longer[m..], shorter), m);
+        return result;
+    }
+
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+    let z0 = mul_limbs(a0, b0);
+    let z2 = mul_limbs(a1, b1);
+    let z1 = mul_limbs(&add_limbs(a0, a1), &add_limbs(b0, b1));
+    let z1 = sub_limbs(&sub_limbs(&z1, &z0), &z2);
+
+    let mut result = z0;
+    add_limbs_at(&mut result, &z1, m);
+    add_limbs_at(&mut result, &z2, 2 * m);
+    result
+}
+
+fn schoolbook_mul_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, &a_limb) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (target, &b_limb) in result[i..i + b.len()].iter_mut().zip(b) {
+            let current = *target as u64 + a_limb as u64 * b_limb as u64 + carry;
+            *target = current as u32;
+            carry = current >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    result
+}
+
+fn add_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = a.to_vec();
+    add_limbs_at(&mut result, b, 0);
+    result
+}
+
+/// Adds value * 2^(32 * offset) to target, growing it as needed.
+fn add_limbs_at(target: &mut Vec<u32>, value: &[u32], offset: usize) {
+    if target.len() < offset + value.len() {
+        target.resize(offset + value.len(), 0);
+    }
+
+    let mut carry = 0u64;
+    let mut index = offset;
+    for &limb in value {
+        let sum = target[index] as u64 + limb as u64 + carry;
+        target[index] = sum as u32;
+        carry = sum >> 32;
+        index += 1;
+    }
+    while carry > 0 {
+        if index == target.len() {
+            target.push(0);
+        }
+        let sum = target[index] as u64 + carry;
+        target[index] = sum as u32;
+        carry = sum >> 32;
+        index += 1;
+    }
+}
+
+/// a - b, where a must be at least b. Trailing zero limbs of b are allowed.
+fn sub_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = a.to_vec();
+    let mut borrow = 0i64;
+    for (index, limb) in result.iter_mut().enumerate() {
+        let difference = *limb as i64 - *b.get(index).unwrap_or(&0) as i64 - borrow;
+        if difference < 0 {
+            *limb = (difference + (1 << 32)) as u32;
+            borrow = 1;
+        } else {
+            *limb = difference as u32;
+            borrow = 0;
+        }
+    }
+    debug_assert!(borrow == 0 && b.iter().skip(a.len()).all(|&limb| limb == 0));
+    result
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // No trailing zero limbs, so more limbs means a bigger number.
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/*
+Reciprocal by Newton's method:
+For d with b bits, approximates 2^(2b) / d. The reciprocal of the top half of d's bits (plus a few
+guard bits), scaled up, already has about b / 2 correct bits, and one Newton step
+x + x * (1 - d * x) doubles that, so the result is within a few of 2^(2b) / d. Dividing any value
+below 2^(2b) by d then takes one multiplication by the reciprocal and a few correction steps.
+
+Time complexity: O(M(N)) for N limbs, where M(N) is the cost of a multiplication, since the
+recursion halves the size every time.
+*/
+fn reciprocal(d: &BigUint) -> BigUint {
+    let bits = d.bits();
+    if bits <= 62 {
+        let d = d.to_u64().expect("fits in 62 bits") as u128;
+        return BigUint::from(((1u128 << (2 * bits)) / d) as u64);
+    }
+
+    // The guess is half_reciprocal * 2^low_bits, which is kept unshifted so that multiplying by
+    // it doesn't go through the zero low limbs. Newton squares the relative error, so an error of
+    // e in the half size reciprocal comes out as about e^2 / 2^(2 * GUARD_BITS) here, and the
+    // guard bits stop it from growing over the levels of recursion.
+    const GUARD_BITS: u64 = 16;
+    let low_bits = bits - (bits.div_ceil(2) + GUARD_BITS);
+    let half_reciprocal = reciprocal(&d.shr_bits(low_bits));
+    let guess = half_reciprocal.shl_bits(low_bits);
+
+    let scale = BigUint::one().shl_bits(2 * bits - low_bits);
+    let product = d * &half_reciprocal;
+    let shift = 2 * (bits - low_bits);
+    if product <= scale {
+        &guess + &(&half_reciprocal * &(&scale - &product)).shr_bits(shift)
+    } else {
+        &guess - &(&half_reciprocal * &(&product - &scale)).shr_bits(shift)
+    }
+}
+
+/// Returns (value / d, value % d) for value < d^2, given reciprocal(d).
+fn div_rem_by_reciprocal(value: &BigUint, d: &BigUint, reciprocal: &BigUint) -> (BigUint, BigUint) {
+    // Only the top bits of value and the reciprocal affect the quotient, which has about
+    // value.bits() - d.bits() bits, so keep that many plus some guard bits.
+    let kept_bits = value.bits().saturating_sub(d.bits()) + 64;
+    let value_shift = value.bits().saturating_sub(kept_bits);
+    let reciprocal_shift = reciprocal.bits().saturating_sub(kept_bits);
+    let product = &value.shr_bits(value_shift) * &reciprocal.shr_bits(reciprocal_shift);
+    let mut quotient = product.shr_bits(2 * d.bits() - value_shift - reciprocal_shift);
+    let mut product = &quotient * d;
+    while product > *value {
+        quotient = &quotient - &BigUint::one();
+        product = &product - d;
+    }
+    let mut remainder = value - &product;
+    while remainder >= *d {
+        quotient = &quotient + &BigUint::one();
+        remainder = &remainder - d;
+    }
+    (quotient, remainder)
+}
+
+/// Decimal conversion peels off 9 digits at a time once the number is below this many limbs.
+const DECIMAL_SPLIT_THRESHOLD: usize = 32;
+
+/// Each decimal chunk holds 9 digits.
+const CHUNK: u32 = 1_000_000_000;
+
+/*
+Decimal conversion:
+Peeling off 9 digits at a time divides the whole number once per chunk, which is O(N^2). Instead
+split on the precomputed powers P_k = 10^(9 * 2^k): a value below P_k^2 is high * P_k + low, and
+high and low are converted recursively with low padded to exactly 2^k chunks. Each level divides
+numbers that add up to the whole value by the same power, using its reciprocal.
+
+Time complexity: O(M(N) log N) for N limbs.
+*/
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+
+        // Up to the first power whose square is bigger than self.
+        let mut powers = vec![BigUint::from(CHUNK as u64)];
+        loop {
+            let last = powers.last().expect("non-empty");
+            let square = last * last;
+            if square > *self {
+                break;
+            }
+            powers.push(square);
+        }
+        let powers: Vec<(BigUint, BigUint)> = powers
+            .into_iter()
+            .map(|power| {
+                let reciprocal = reciprocal(&power);
+                (power, reciprocal)
+            })
+            .collect();
+
+        let mut chunks = vec![];
+        push_chunks(self, powers.len() - 1, &powers, false, &mut chunks);
+
+        let mut digits = chunks[0].to_string();
+        for chunk in &chunks[1..] {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+/// Pushes the decimal chunks of value < P_level^2, most significant first. With pad it's exactly
+/// 2^(level + 1) chunks, otherwise there are no leading zero chunks.
+fn push_chunks(
+    value: &BigUint,
+    level: usize,
+    powers: &[(BigUint, BigUint)],
+    pad: bool,
+    chunks: &mut Vec<u32>,
+) {
+    let (power, reciprocal) = &powers[level];
+    if power.limbs.len() < DECIMAL_SPLIT_THRESHOLD {
+        // Least significant chunk first.
+        let mut small = vec![];
+        let mut remaining = value.clone();
+        while !remaining.is_zero() {
+            let (quotient, chunk) = remaining.div_rem_small(CHUNK);
+            small.push(chunk);
+            remaining = quotient;
+        }
+        if pad {
+            small.resize(2 << level, 0);
+        }
+        chunks.extend(small.iter().rev());
+        return;
+    }
+
+    if !pad && value < power {
+        return push_chunks(value, level - 1, powers, false, chunks);
+    }
+    let (high, low) = div_rem_by_reciprocal(value, power, reciprocal);
+    push_chunks(&high, level - 1, powers, pad, chunks);
+    push_chunks(&low, level - 1, powers, true, chunks);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_mix::SplitMix64;
+
+    #[test]
+    fn arithmetic_matches_u128() {
+        let a = BigUint::from(u64::MAX);
+        let b = BigUint::from(12_345_678_901);
+
+        assert_eq!(
+            (&a + &b).to_string(),
+            (u64::MAX as u128 + 12_345_678_901).to_string()
+        );
+        assert_eq!(
+            a.mul_small(u32::MAX).to_string(),
+            (u64::MAX as u128 * u32::MAX as u128).to_string()
+        );
+
+        let (quotient, remainder) = a.mul_small(1000).div_rem_small(7);
+        let expected = u64::MAX as u128 * 1000;
+        assert_eq!(quotient.to_string(), (expected / 7).to_string());
+        assert_eq!(remainder as u128, expected % 7);
+    }
+
+    #[test]
+    fn displays_zero_padded_chunks() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        let mut value = BigUint::one();
+        for _ in 0..30 {
+            value = value.mul_small(10);
+        }
+        assert_eq!(value.to_string(), format!("1{}", "0".repeat(30)));
+    }
+
+    /// Peels off one digit at a time.
+    fn naive_decimal(value: &BigUint) -> String {
+        let mut digits = vec![];
+        let mut remaining = value.clone();
+        while !remaining.is_zero() {
+            let (quotient, digit) = remaining.div_rem_small(10);
+            digits.push(std::char::from_digit(digit, 10).expect("decimal digit"));
+            remaining = quotient;
+        }
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        digits.iter().rev().collect()
+    }
+
+    #[test]
+    fn displays_large_values_like_naive_conversion() {
+        let mut rng = SplitMix64::new(39);
+        for &limbs in &[1, 2, 31, 32, 33, 64, 100, 257, 700] {
+            let value = pseudo_random(limbs, &mut rng);
+            assert_eq!(
+                value.to_string(),
+                naive_decimal(&value),
+                "limbs = {}",
+                limbs
+            );
+        }
+
+        // Around the powers that split the value, where padding goes wrong first.
+        let mut power = BigUint::one();
+        for digits in 1..=1_200 {
+            power = power.mul_small(10);
+            if digits % 9 != 0 {
+                continue;
+            }
+            let below = &power - &BigUint::one();
+            assert_eq!(power.to_string(), format!("1{}", "0".repeat(digits)));
+            assert_eq!(below.to_string(), "9".repeat(digits));
+            assert_eq!(
+                (&power + &below).to_string(),
+                format!("1{}", "9".repeat(digits))
+            );
+        }
+    }
+
+    #[test]
+    fn reciprocal_divides_exactly() {
+        let mut rng = SplitMix64::new(41);
+        for &limbs in &[1, 2, 3, 40, 150] {
+            let d = pseudo_random(limbs, &mut rng);
+            let d_reciprocal = reciprocal(&d);
+            // Within a few of 2^(2b) / d.
+            let scale = BigUint::one().shl_bits(2 * d.bits());
+            let product = &d * &d_reciprocal;
+            let error = if product > scale {
+                &product - &scale
+            } else {
+                &scale - &product
+            };
+            assert!(error < d.mul_small(4), "limbs = {}", limbs);
+
+            let square = &d * &d;
+            for value in &[
+                &square - &BigUint::one(),
+                square.shr_bits(d.bits() / 2),
+                &d - &BigUint::one(),
+            ] {
+                let (quotient, remainder) = div_rem_by_reciprocal(value, &d, &d_reciprocal);
+                assert!(remainder < d);
+                assert_eq!(&(&quotient * &d) + &remainder, *value);
+            }
+        }
+        assert_eq!(
+            BigUint::from(5).shl_bits(70).shr_bits(69),
+            BigUint::from(10)
+        );
+        assert_eq!(BigUint::from(5).shr_bits(3), BigUint::zero());
+    }
+
+    #[test]
+    fn orders_numerically() {
+        assert!(BigUint::from(1 << 32) > BigUint::from(u32::MAX as u64));
+        assert!(BigUint::from(5) < BigUint::from(6));
+        assert_eq!(BigUint::from(0), BigUint::zero());
+        assert_eq!(BigUint::from(7).to_u64(), Some(7));
+        assert_eq!(BigUint::from(u64::MAX).mul_small(2).to_u64(), None);
+    }
+
+    /// Deterministic pseudo-random value with the given number of limbs.
+    fn pseudo_random(limbs: usize, rng: &mut SplitMix64) -> BigUint {
+        let limbs = (0..limbs).map(|_| rng.next_u64() as u32).collect();
+        BigUint::from_limbs(limbs)
+    }
+
+    #[test]
+    fn karatsuba_matches_schoolbook() {
+        let mut rng = SplitMix64::new(7);
+        for &(a_len, b_len) in &[
+            (32, 32),
+            (33, 70),
+            (100, 100),
+            (200, 31),
+            (257, 129),
+            (1, 300),
+        ] {
+            let a = pseudo_random(a_len, &mut rng);
+            let b = pseudo_random(b_len, &mut rng);
+            let expected = BigUint::from_limbs(schoolbook_mul_limbs(&a.limbs, &b.limbs));
+            assert_eq!(&a * &b, expected);
+            assert_eq!(&b * &a, expected);
+        }
+    }
+
+    #[test]
+    fn subtraction_undoes_addition() {
+        let mut rng = SplitMix64::new(11);
+        let a = pseudo_random(50, &mut rng);
+        let b = pseudo_random(20, &mut rng);
+        assert_eq!(&(&a + &b) - &b, a);
+        assert_eq!(&a - &a, BigUint::zero());
+        assert_eq!(
+            BigUint::from(1 << 32) - BigUint::one(),
+            BigUint::from(u32::MAX as u64)
+        );
+        assert_eq!(BigUint::from(1 << 32).bits(), 33);
+        assert_eq!(BigUint::zero().bits(), 0);
+    }
+}
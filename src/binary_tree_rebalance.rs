@@ -0,0 +1,108 @@
+use ego_binary_tree::{BinaryNodeMut, BinaryNodeRef, BinaryTree};
+
+/*
+Problem: Given any binary tree, return a height-balanced tree with the same in-order sequence.
+
+Solution:
+Collect the values in-order, then rebuild the tree by recursively picking the middle value of
+each range as the subtree root. The two halves of every range differ in size by at most one,
+so every level is full except possibly the last. This keeps all leaves within one level of each
+other (superbalanced) and, since the in-order sequence is preserved, a search tree stays a
+search tree.
+
+The in-order collection uses an explicit stack because the trees that most need rebalancing
+are the degenerate ones, whose height is close to N.
+
+Time complexity: O(N).
+Space complexity: O(N) for the collected values.
+*/
+pub fn rebalance<T: Clone>(tree: &BinaryTree<T>) -> BinaryTree<T> {
+    let values = in_order_values(tree);
+
+    // The tree always contains at least one node, so there is always a middle value.
+    let middle = values.len() / 2;
+    let mut balanced = BinaryTree::new(values[middle].clone());
+    build_children(
+        &mut balanced.root_mut(),
+        &values[..middle],
+        &values[middle + 1..],
+    );
+    balanced
+}
+
+fn in_order_values<T: Clone>(tree: &BinaryTree<T>) -> Vec<T> {
+    let mut values = vec![];
+    let mut stack: Vec<BinaryNodeRef<T>> = vec![];
+    let mut current = Some(tree.root());
+
+    while current.is_some() || !stack.is_empty() {
+        // Walk as far left as possible, the top of the stack is then the next in-order node.
+        while let Some(node) = current {
+            current = node.left();
+            stack.push(node);
+        }
+
+        let node = stack
+            .pop()
+            .expect("stack is non-empty when current is none");
+        values.push(node.value().clone());
+        current = node.right();
+    }
+
+    values
+}
+
+fn build_children<T: Clone>(node: &mut BinaryNodeMut<T>, left: &[T], right: &[T]) {
+    if !left.is_empty() {
+        let middle = left.len() / 2;
+        let mut child = node.set_left(left[middle].clone());
+        build_children(&mut child, &left[..middle], &left[middle + 1..]);
+    }
+    if !right.is_empty() {
+        let middle = right.len() / 2;
+        let mut child = node.set_right(right[middle].clone());
+        build_children(&mut child, &right[..middle], &right[middle + 1..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_tree_search::is_binary_search_tree;
+    use crate::binary_tree_superbalanced::{balance_report, is_superbalanced};
+    use crate::test_trees::right_chain;
+    use ego_binary_tree::binary_tree;
+
+    #[test]
+    fn rebalances_chains_of_every_length() {
+        for len in 1..=64 {
+            let tree = right_chain(len);
+            let balanced = rebalance(&tree);
+
+            assert_eq!(in_order_values(&balanced), in_order_values(&tree));
+            assert!(is_binary_search_tree(&balanced));
+            let report = balance_report(&balanced);
+            assert!(report.is_superbalanced, "len {}", len);
+            assert!(report.is_height_balanced, "len {}", len);
+            assert_eq!(report.node_count, len as usize);
+        }
+    }
+
+    #[test]
+    fn keeps_in_order_sequence_of_non_search_tree() {
+        let tree = binary_tree! {
+            "a" => {
+                left: "b" => {
+                    left: "c" => {
+                        right: "d",
+                    },
+                },
+                right: "e",
+            }
+        };
+        let balanced = rebalance(&tree);
+
+        assert_eq!(in_order_values(&balanced), vec!["c", "d", "b", "a", "e"]);
+        assert!(is_superbalanced(&balanced));
+    }
+}
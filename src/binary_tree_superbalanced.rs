@@ -1,46 +1,182 @@
 use ego_binary_tree::{BinaryNodeRef, BinaryTree};
+use std::cmp;
+use std::collections::VecDeque;
 
 pub fn is_superbalanced<T>(tree: &BinaryTree<T>) -> bool {
-    let mut min_leaf_depth = None;
-    let mut max_leaf_depth = None;
-
-    // Min and max must be populated by this function.
-    populate_min_max_leaf_height(tree.root(), 0, &mut min_leaf_depth, &mut max_leaf_depth);
+    balance_report(tree).is_superbalanced
+}
 
-    let height_diff = max_leaf_depth.expect("exists") - min_leaf_depth.expect("exists");
-    height_diff <= 1
+/// Result of is_superbalanced_iterative, along with how much of the tree had to be looked at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuperbalancedCheck {
+    pub is_superbalanced: bool,
+    pub nodes_visited: usize,
 }
 
-fn populate_min_max_leaf_height<T>(
-    node: BinaryNodeRef<T>,
-    depth: u32,
-    min_leaf_depth: &mut Option<u32>,
-    max_leaf_depth: &mut Option<u32>,
-) {
-    let left = node.left();
-    let right = node.right();
-    if left.is_none() && right.is_none() {
-        // Found leaf node, populate min / max if necessary.
-        if min_leaf_depth.is_none() || depth < min_leaf_depth.expect("exists") {
-            *min_leaf_depth = Some(depth);
+/*
+Iterative solution:
+Breadth-first traversal visits nodes in order of depth, so the first leaf found is at the minimum
+leaf depth. Any node more than one level below that leaf must have a leaf below it at least as deep,
+so the tree can be rejected as soon as such a node is dequeued, without looking at the rest of it.
+This also covers seeing three distinct leaf depths, which can't happen without two of them being
+more than one apart.
+
+There is no recursion, so trees of arbitrary depth (e.g. a chain of a million nodes) are fine.
+
+Time complexity: O(N) worst case, but stops early on lopsided trees.
+Space complexity: O(W) where W is the widest level of the tree.
+*/
+pub fn is_superbalanced_iterative<T>(tree: &BinaryTree<T>) -> SuperbalancedCheck {
+    let mut min_leaf_depth: Option<u32> = None;
+    let mut nodes_visited = 0;
+
+    let mut nodes = VecDeque::new();
+    nodes.push_back((tree.root(), 0));
+
+    while let Some((node, depth)) = nodes.pop_front() {
+        if let Some(min_leaf_depth) = min_leaf_depth {
+            if depth > min_leaf_depth + 1 {
+                return SuperbalancedCheck {
+                    is_superbalanced: false,
+                    nodes_visited,
+                };
+            }
+        }
+
+        nodes_visited += 1;
+        let left = node.left();
+        let right = node.right();
+        if left.is_none() && right.is_none() && min_leaf_depth.is_none() {
+            min_leaf_depth = Some(depth);
+        }
+
+        if let Some(left) = left {
+            nodes.push_back((left, depth + 1));
         }
-        if max_leaf_depth.is_none() || depth > max_leaf_depth.expect("exists") {
-            *max_leaf_depth = Some(depth);
+        if let Some(right) = right {
+            nodes.push_back((right, depth + 1));
         }
-        return;
     }
 
-    if let Some(left) = left {
-        populate_min_max_leaf_height(left, depth + 1, min_leaf_depth, max_leaf_depth);
+    SuperbalancedCheck {
+        is_superbalanced: true,
+        nodes_visited,
+    }
+}
+
+/// Structural summary of a binary tree. Depths and heights count edges, so the root is at depth 0
+/// and a single-node tree has height 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceReport {
+    pub height: u32,
+    pub min_leaf_depth: u32,
+    pub max_leaf_depth: u32,
+    pub node_count: usize,
+    pub leaf_count: usize,
+    /// Height of the right subtree minus height of the left subtree for every node, in pre-order.
+    /// A missing subtree has height -1.
+    pub balance_factors: Vec<i32>,
+    /// Leaf depths differ by at most one.
+    pub is_superbalanced: bool,
+    /// Every node has a balance factor in -1..=1 (AVL).
+    pub is_height_balanced: bool,
+    /// Every level is full except possibly the last, which is filled from the left.
+    pub is_complete: bool,
+    /// Every level is full.
+    pub is_perfect: bool,
+    /// Every node has either zero or two children.
+    pub is_full: bool,
+}
+
+/// Computes the BalanceReport in a single post-order traversal.
+pub fn balance_report<T>(tree: &BinaryTree<T>) -> BalanceReport {
+    let mut report = BalanceReport {
+        height: 0,
+        min_leaf_depth: u32::MAX,
+        max_leaf_depth: 0,
+        node_count: 0,
+        leaf_count: 0,
+        balance_factors: vec![],
+        is_superbalanced: false,
+        is_height_balanced: false,
+        is_complete: false,
+        is_perfect: false,
+        is_full: false,
+    };
+
+    // The tree always contains at least one node, so min / max leaf depth are always populated.
+    let root = summarize_subtree(Some(tree.root()), 0, &mut report);
+    report.height = root.height as u32;
+    report.is_superbalanced = report.max_leaf_depth - report.min_leaf_depth <= 1;
+    report.is_height_balanced = root.is_height_balanced;
+    report.is_complete = root.is_complete;
+    report.is_perfect = root.is_perfect;
+    report.is_full = root.is_full;
+    report
+}
+
+struct SubtreeSummary {
+    /// -1 for a missing subtree.
+    height: i32,
+    is_height_balanced: bool,
+    is_complete: bool,
+    is_perfect: bool,
+    is_full: bool,
+}
+
+fn summarize_subtree<T>(
+    node: Option<BinaryNodeRef<T>>,
+    depth: u32,
+    report: &mut BalanceReport,
+) -> SubtreeSummary {
+    let node = if let Some(node) = node {
+        node
+    } else {
+        return SubtreeSummary {
+            height: -1,
+            is_height_balanced: true,
+            is_complete: true,
+            is_perfect: true,
+            is_full: true,
+        };
+    };
+
+    // Reserve the pre-order slot, the balance factor is only known once both children are visited.
+    let balance_factor_index = report.balance_factors.len();
+    report.balance_factors.push(0);
+    report.node_count += 1;
+
+    let left = summarize_subtree(node.left(), depth + 1, report);
+    let right = summarize_subtree(node.right(), depth + 1, report);
+
+    let is_leaf = node.left().is_none() && node.right().is_none();
+    if is_leaf {
+        report.leaf_count += 1;
+        report.min_leaf_depth = cmp::min(report.min_leaf_depth, depth);
+        report.max_leaf_depth = cmp::max(report.max_leaf_depth, depth);
     }
-    if let Some(right) = right {
-        populate_min_max_leaf_height(right, depth + 1, min_leaf_depth, max_leaf_depth);
+
+    let balance_factor = right.height - left.height;
+    report.balance_factors[balance_factor_index] = balance_factor;
+
+    SubtreeSummary {
+        height: 1 + cmp::max(left.height, right.height),
+        is_height_balanced: left.is_height_balanced
+            && right.is_height_balanced
+            && balance_factor.abs() <= 1,
+        // Either the left subtree is perfect and the last level continues into the right subtree,
+        // or the last level ends in the left subtree and the right subtree is one level shorter.
+        is_complete: (left.is_perfect && right.is_complete && left.height == right.height)
+            || (left.is_complete && right.is_perfect && left.height == right.height + 1),
+        is_perfect: left.is_perfect && right.is_perfect && left.height == right.height,
+        is_full: left.is_full && right.is_full && node.left().is_some() == node.right().is_some(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_trees::{append_right_chain, deep_right_chain};
     use ego_binary_tree::binary_tree;
 
     #[test]
@@ -117,4 +253,135 @@ mod tests {
         };
         assert_eq!(is_superbalanced(&tree), true);
     }
+
+    #[test]
+    fn report_on_perfect_tree() {
+        let tree = binary_tree! {
+            4 => {
+                left: 2 => {
+                    left: 1,
+                    right: 3,
+                },
+                right: 6 => {
+                    left: 5,
+                    right: 7,
+                },
+            }
+        };
+        let report = balance_report(&tree);
+        assert_eq!(report.height, 2);
+        assert_eq!(report.min_leaf_depth, 2);
+        assert_eq!(report.max_leaf_depth, 2);
+        assert_eq!(report.node_count, 7);
+        assert_eq!(report.leaf_count, 4);
+        assert_eq!(report.balance_factors, vec![0; 7]);
+        assert!(report.is_superbalanced);
+        assert!(report.is_height_balanced);
+        assert!(report.is_complete);
+        assert!(report.is_perfect);
+        assert!(report.is_full);
+    }
+
+    #[test]
+    fn report_on_complete_tree() {
+        let tree = binary_tree! {
+            "root" => {
+                left: "l" => {
+                    left: "ll",
+                },
+                right: "r",
+            }
+        };
+        let report = balance_report(&tree);
+        assert_eq!(report.height, 2);
+        assert_eq!(report.min_leaf_depth, 1);
+        assert_eq!(report.max_leaf_depth, 2);
+        assert_eq!(report.leaf_count, 2);
+        assert_eq!(report.balance_factors, vec![-1, -1, 0, 0]);
+        assert!(report.is_superbalanced);
+        assert!(report.is_height_balanced);
+        assert!(report.is_complete);
+        assert!(!report.is_perfect);
+        assert!(!report.is_full);
+    }
+
+    #[test]
+    fn report_on_lopsided_tree() {
+        let tree = binary_tree! {
+            "root" => {
+                right: "r" => {
+                    left: "rl",
+                    right: "rr",
+                },
+            }
+        };
+        let report = balance_report(&tree);
+        assert_eq!(report.height, 2);
+        assert_eq!(report.min_leaf_depth, 2);
+        assert_eq!(report.max_leaf_depth, 2);
+        assert_eq!(report.node_count, 4);
+        assert_eq!(report.balance_factors, vec![2, 0, 0, 0]);
+        // Superbalanced only looks at leaves, so the missing left subtree goes unnoticed.
+        assert!(report.is_superbalanced);
+        assert!(!report.is_height_balanced);
+        assert!(!report.is_complete);
+        assert!(!report.is_perfect);
+        assert!(!report.is_full);
+    }
+
+    #[test]
+    fn iterative_matches_recursive() {
+        let not_superbalanced = binary_tree! {
+            "root" => {
+                left: "left",
+                right: "right" => {
+                    left: "rightleft" => {
+                        left: "rightleftleft",
+                        right: "rightleftright"
+                    },
+                    right: "rightright",
+                }
+            }
+        };
+        let superbalanced = binary_tree! {
+            "root" => {
+                left: "left",
+                right: "right" => {
+                    left: "rightleft",
+                    right: "rightright",
+                }
+            }
+        };
+
+        for tree in &[not_superbalanced, superbalanced] {
+            let check = is_superbalanced_iterative(tree);
+            assert_eq!(check.is_superbalanced, is_superbalanced(tree));
+        }
+    }
+
+    #[test]
+    fn iterative_exits_early_on_lopsided_tree() {
+        let mut tree = BinaryTree::new(0);
+        {
+            let mut root = tree.root_mut();
+            root.set_left(0);
+            let chain: Vec<u32> = (1..=1000).collect();
+            append_right_chain(&mut root, &chain);
+        }
+
+        let check = is_superbalanced_iterative(&tree);
+        assert!(!check.is_superbalanced);
+        // The left leaf is at depth 1, so the chain is rejected once it reaches depth 3.
+        assert_eq!(check.nodes_visited, 4);
+    }
+
+    #[test]
+    fn iterative_handles_million_node_chain() {
+        // A chain has a single leaf, so it's superbalanced however deep it is.
+        let tree = deep_right_chain(1_000_000);
+
+        let check = is_superbalanced_iterative(&tree);
+        assert!(check.is_superbalanced);
+        assert_eq!(check.nodes_visited, 1_000_000);
+    }
 }
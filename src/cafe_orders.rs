@@ -1,28 +1,442 @@
+use crate::big_uint::BigUint;
+use crate::split_mix::SplitMix64;
+use std::collections::{HashMap, HashSet, VecDeque};
+
 pub fn validate_fifo_orders(take_out: Vec<i32>, dine_in: Vec<i32>, served: Vec<i32>) -> bool {
-    let mut take_out_index = 0;
-    let mut dine_in_index = 0;
+    validate_fifo_queues(&[("take_out", &take_out), ("dine_in", &dine_in)], &served)
+}
+
+/// Checks that every named queue (e.g. take out, dine in, drive thru) was served in fifo order.
+pub fn validate_fifo_queues(queues: &[(&str, &[i32])], served: &[i32]) -> bool {
+    fifo_interleaving(queues, served).is_some()
+}
+
+/*
+Greedily advancing a pointer per queue breaks down when the same id is at the head of more than
+one queue: advancing all of them counts one served order twice, and advancing only one of them
+might pick the wrong queue. Instead, search over the queue positions, backtracking when a choice
+leads to a dead end.
+
+A state is the position in each queue, the position in served is their sum. States that are known
+to be dead ends are remembered so each one is only explored once.
 
-    for id in served {
-        // If this id is not found in the pointers for take_out / dine_in,
-        // then the customers were not served in fifo order.
-        let was_take_out = take_out_index < take_out.len() && id == take_out[take_out_index];
-        let was_dine_in = dine_in_index < dine_in.len() && id == dine_in[dine_in_index];
+Time complexity: O(S * Q) where S is the number of reachable states and Q the number of queues.
+Without repeated ids there is only one choice per served order, so S is O(N).
+Space complexity: O(S * Q).
+*/
+/// Returns the name of the queue each served order came from, if served is a fifo interleaving of
+/// the queues. Served may stop before all of the queues are empty.
+pub fn fifo_interleaving<'a>(queues: &[(&'a str, &[i32])], served: &[i32]) -> Option<Vec<&'a str>> {
+    let search = search_interleaving(queues, served);
+    if !search.found {
+        return None;
+    }
+
+    Some(
+        search
+            .choices
+            .into_iter()
+            .map(|queue| queues[queue].0)
+            .collect(),
+    )
+}
+
+/// Diagnostics for a served sequence, see validate_fifo_report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FifoReport<'a> {
+    /// Queue each served order came from. Empty if there was a violation.
+    pub served_from: Vec<&'a str>,
+    /// Position in served of the first order that no fifo interleaving can explain.
+    pub first_violation: Option<usize>,
+    /// Head of each queue at the first violation, or after the last served order if there was
+    /// none. None if the queue was empty at that point.
+    pub expected_heads: Vec<(&'a str, Option<i32>)>,
+    /// Position and id of each served order that isn't in any queue.
+    pub unknown_ids: Vec<(usize, i32)>,
+    /// Orders left in each queue that were never served. Only populated in strict mode.
+    pub unserved: Vec<(&'a str, Vec<i32>)>,
+}
 
-        if !was_take_out && !was_dine_in {
-            return false;
+impl<'a> FifoReport<'a> {
+    pub fn is_valid(&self) -> bool {
+        self.first_violation.is_none() && self.unserved.is_empty()
+    }
+}
+
+/// Like fifo_interleaving, but explains what went wrong. In strict mode every queued order must
+/// be served exactly once.
+pub fn validate_fifo_report<'a>(
+    queues: &[(&'a str, &[i32])],
+    served: &[i32],
+    strict: bool,
+) -> FifoReport<'a> {
+    let search = search_interleaving(queues, served);
+
+    let known_ids: HashSet<i32> = queues
+        .iter()
+        .flat_map(|(_name, orders)| orders.iter().cloned())
+        .collect();
+    let unknown_ids = served
+        .iter()
+        .enumerate()
+        .filter(|(_position, id)| !known_ids.contains(id))
+        .map(|(position, &id)| (position, id))
+        .collect();
+
+    let expected_heads = queues
+        .iter()
+        .zip(search.deepest_indices.iter())
+        .map(|((name, orders), &index)| (*name, orders.get(index).cloned()))
+        .collect();
+
+    let mut unserved = vec![];
+    if strict {
+        for ((name, orders), &index) in queues.iter().zip(search.deepest_indices.iter()) {
+            if index < orders.len() {
+                unserved.push((*name, orders[index..].to_vec()));
+            }
         }
+    }
+
+    let (served_from, first_violation) = if search.found {
+        let served_from = search
+            .choices
+            .into_iter()
+            .map(|queue| queues[queue].0)
+            .collect();
+        (served_from, None)
+    } else {
+        (vec![], Some(search.deepest))
+    };
 
-        if was_take_out {
-            take_out_index += 1;
+    FifoReport {
+        served_from,
+        first_violation,
+        expected_heads,
+        unknown_ids,
+        unserved,
+    }
+}
+
+struct InterleavingSearch {
+    found: bool,
+    /// Queue index for each served order, if found.
+    choices: Vec<usize>,
+    /// Number of served orders in the longest valid prefix.
+    deepest: usize,
+    /// Queue positions at the end of the longest valid prefix.
+    deepest_indices: Vec<usize>,
+}
+
+fn search_interleaving(queues: &[(&str, &[i32])], served: &[i32]) -> InterleavingSearch {
+    let mut indices = vec![0; queues.len()];
+    let mut choices: Vec<usize> = vec![];
+    let mut dead_ends: HashSet<Vec<usize>> = HashSet::new();
+
+    let mut deepest = 0;
+    let mut deepest_indices = indices.clone();
+
+    // Iterative depth-first search, so long served sequences don't overflow the stack.
+    let mut next_queue = 0;
+    loop {
+        let position = choices.len();
+        if position == served.len() {
+            return InterleavingSearch {
+                found: true,
+                choices,
+                deepest,
+                deepest_indices,
+            };
+        }
+
+        let id = served[position];
+        let choice = (next_queue..queues.len()).find(|&queue| {
+            let orders = queues[queue].1;
+            if indices[queue] >= orders.len() || orders[indices[queue]] != id {
+                return false;
+            }
+
+            indices[queue] += 1;
+            let is_dead_end = dead_ends.contains(&indices);
+            indices[queue] -= 1;
+            !is_dead_end
+        });
+
+        if let Some(queue) = choice {
+            indices[queue] += 1;
+            choices.push(queue);
+            next_queue = 0;
+
+            if choices.len() > deepest {
+                deepest = choices.len();
+                deepest_indices = indices.clone();
+            }
+            continue;
         }
-        if was_dine_in {
-            dine_in_index += 1;
+
+        // No queue can serve this order from here, backtrack and try the next queue instead.
+        dead_ends.insert(indices.clone());
+        match choices.pop() {
+            Some(queue) => {
+                indices[queue] -= 1;
+                next_queue = queue + 1;
+            }
+            None => {
+                return InterleavingSearch {
+                    found: false,
+                    choices,
+                    deepest,
+                    deepest_indices,
+                }
+            }
+        }
+    }
+}
+
+/*
+Counting assignments:
+Serving every order in fifo order is the same as choosing which queue each served order comes
+from, where queue i is chosen exactly n_i times. So the number of assignments is the multinomial
+(n_1 + ... + n_k)! / (n_1! * ... * n_k!), which quickly outgrows any fixed-size integer.
+
+It's built up one order at a time: adding an order to queue i multiplies the count by
+(orders so far) / (orders so far in queue i), and every intermediate value is itself a product
+of binomials, so each division is exact.
+
+When every id is unique, each assignment gives a different served sequence, so these count,
+enumerate and sample exactly the sequences validate_fifo_queues accepts. With the same id in more
+than one queue, different assignments can give the same served sequence: the count is then an
+upper bound on the number of valid sequences, the iterator yields such a sequence once per
+assignment, and the sampler favors it in proportion.
+*/
+/// Number of ways to assign every served order to a queue while keeping each queue in fifo order.
+pub fn count_fifo_assignments(queues: &[(&str, &[i32])]) -> BigUint {
+    let mut count = BigUint::one();
+    let mut total = 0;
+    for (_name, orders) in queues {
+        for served_from_queue in 1..=orders.len() as u32 {
+            total += 1;
+            count = count.mul_small(total).div_rem_small(served_from_queue).0;
+        }
+    }
+    count
+}
+
+/// Lazily yields the served sequence of every fifo assignment, see fifo_assignments.
+pub struct FifoAssignments<'a> {
+    queues: &'a [(&'a str, &'a [i32])],
+    /// Queue index for each served order of the next assignment, None once exhausted.
+    choices: Option<Vec<usize>>,
+}
+
+/// Iterates over the assignments in lexicographic order of the queue each order is served from,
+/// yielding the served sequence of each.
+pub fn fifo_assignments<'a>(queues: &'a [(&'a str, &'a [i32])]) -> FifoAssignments<'a> {
+    let mut choices = vec![];
+    for (queue, (_name, orders)) in queues.iter().enumerate() {
+        choices.extend(std::iter::repeat_n(queue, orders.len()));
+    }
+
+    FifoAssignments {
+        queues,
+        choices: Some(choices),
+    }
+}
+
+impl<'a> Iterator for FifoAssignments<'a> {
+    type Item = Vec<i32>;
+
+    fn next(&mut self) -> Option<Vec<i32>> {
+        let choices = self.choices.as_mut()?;
+        let served = serve_choices(self.queues, choices);
+        if !next_permutation(choices) {
+            self.choices = None;
         }
+        Some(served)
     }
+}
+
+/// Rearranges into the next lexicographic permutation, returns false if already the last one.
+fn next_permutation(values: &mut [usize]) -> bool {
+    // Find the rightmost ascent, everything after it is in descending order.
+    let pivot = match (0..values.len().saturating_sub(1))
+        .rev()
+        .find(|&index| values[index] < values[index + 1])
+    {
+        Some(pivot) => pivot,
+        None => return false,
+    };
 
+    // Swap with the smallest larger value to its right, then put the tail in ascending order.
+    let successor = (pivot + 1..values.len())
+        .rev()
+        .find(|&index| values[index] > values[pivot])
+        .expect("ascent guarantees a larger value");
+    values.swap(pivot, successor);
+    values[pivot + 1..].reverse();
     true
 }
 
+fn serve_choices(queues: &[(&str, &[i32])], choices: &[usize]) -> Vec<i32> {
+    let mut indices = vec![0; queues.len()];
+    choices
+        .iter()
+        .map(|&queue| {
+            let id = queues[queue].1[indices[queue]];
+            indices[queue] += 1;
+            id
+        })
+        .collect()
+}
+
+/// Samples assignments uniformly at random and yields their served sequences. The same seed always
+/// gives the same samples.
+pub struct FifoAssignmentSampler<'a> {
+    queues: &'a [(&'a str, &'a [i32])],
+    rng: SplitMix64,
+}
+
+impl<'a> FifoAssignmentSampler<'a> {
+    pub fn new(queues: &'a [(&'a str, &'a [i32])], seed: u64) -> Self {
+        Self {
+            queues,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// Picking the next queue with probability (orders left in queue) / (orders left overall)
+    /// makes every assignment equally likely: each one has probability
+    /// (n_1! * ... * n_k!) / (n_1 + ... + n_k)!.
+    pub fn sample(&mut self) -> Vec<i32> {
+        let mut remaining: Vec<u64> = self
+            .queues
+            .iter()
+            .map(|(_name, orders)| orders.len() as u64)
+            .collect();
+        let mut total_remaining: u64 = remaining.iter().sum();
+
+        let mut choices = Vec::with_capacity(total_remaining as usize);
+        while total_remaining > 0 {
+            let mut pick = self.rng.next_below(total_remaining);
+            let queue = remaining
+                .iter()
+                .position(|&count| {
+                    if pick < count {
+                        return true;
+                    }
+                    pick -= count;
+                    false
+                })
+                .expect("pick is below total remaining");
+
+            remaining[queue] -= 1;
+            total_remaining -= 1;
+            choices.push(queue);
+        }
+
+        serve_choices(self.queues, &choices)
+    }
+}
+
+/// Order event from a live stream, e.g. a kitchen display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderEvent<T> {
+    Placed { queue: String, id: T },
+    Served { queue: String, id: T },
+}
+
+/// Reported as soon as an order is served out of fifo order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FifoViolation<T> {
+    /// The order is in the queue, but other orders were placed before it.
+    OutOfOrder { queue: String, id: T, expected: T },
+    /// The order was never placed on this queue (or was already served).
+    Unknown { queue: String, id: T },
+}
+
+/// Stateful version of validate_fifo_queues for orders that arrive over time. Instead of a pointer
+/// into each queue, every queue only holds the orders that haven't been served yet.
+#[derive(Debug, Clone)]
+pub struct FifoMonitor<T> {
+    queues: HashMap<String, VecDeque<T>>,
+}
+
+impl<T> Default for FifoMonitor<T> {
+    fn default() -> Self {
+        Self {
+            queues: HashMap::new(),
+        }
+    }
+}
+
+impl<T: PartialEq + Clone> FifoMonitor<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle(&mut self, event: OrderEvent<T>) -> Option<FifoViolation<T>> {
+        match event {
+            OrderEvent::Placed { queue, id } => {
+                self.order_placed(&queue, id);
+                None
+            }
+            OrderEvent::Served { queue, id } => self.order_served(&queue, id),
+        }
+    }
+
+    pub fn order_placed(&mut self, queue: &str, id: T) {
+        self.queues
+            .entry(queue.to_string())
+            .or_default()
+            .push_back(id);
+    }
+
+    /// Returns the violation if the order was not at the head of its queue. The order is removed
+    /// from the queue either way, so later orders are checked against what was actually served.
+    pub fn order_served(&mut self, queue: &str, id: T) -> Option<FifoViolation<T>> {
+        let orders = match self.queues.get_mut(queue) {
+            Some(orders) => orders,
+            None => {
+                return Some(FifoViolation::Unknown {
+                    queue: queue.to_string(),
+                    id,
+                })
+            }
+        };
+
+        let position = match orders.iter().position(|order| order == &id) {
+            Some(position) => position,
+            None => {
+                return Some(FifoViolation::Unknown {
+                    queue: queue.to_string(),
+                    id,
+                })
+            }
+        };
+
+        if position == 0 {
+            orders.pop_front();
+            return None;
+        }
+
+        let expected = orders[0].clone();
+        orders.remove(position);
+        Some(FifoViolation::OutOfOrder {
+            queue: queue.to_string(),
+            id,
+            expected,
+        })
+    }
+
+    /// Orders placed on the queue that haven't been served yet, oldest first.
+    pub fn pending(&self, queue: &str) -> Vec<T> {
+        self.queues
+            .get(queue)
+            .map(|orders| orders.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,4 +464,329 @@ mod tests {
         let is_fifo = validate_fifo_orders(vec![10], vec![2, 4, 6, 4], vec![2, 10, 4, 6, 4]);
         assert_eq!(is_fifo, true);
     }
+
+    #[test]
+    fn many_queues_fifo() {
+        let is_fifo = validate_fifo_queues(
+            &[
+                ("take_out", &[1, 6]),
+                ("dine_in", &[2, 7]),
+                ("drive_thru", &[3]),
+                ("delivery", &[4, 8]),
+                ("mobile_pickup", &[5]),
+            ],
+            &[1, 2, 3, 4, 5, 6, 7, 8],
+        );
+        assert!(is_fifo);
+    }
+
+    #[test]
+    fn many_queues_non_fifo() {
+        let is_fifo = validate_fifo_queues(
+            &[
+                ("take_out", &[1, 6]),
+                ("dine_in", &[2, 7]),
+                ("drive_thru", &[3]),
+                ("delivery", &[4, 8]),
+                ("mobile_pickup", &[5]),
+            ],
+            &[1, 2, 3, 8, 4, 5, 6, 7],
+        );
+        assert!(!is_fifo);
+    }
+
+    #[test]
+    fn same_id_at_head_of_two_queues() {
+        let take_out: &[i32] = &[1, 2];
+        let dine_in: &[i32] = &[1, 3];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        let served_from = fifo_interleaving(&queues, &[1, 2, 1, 3]);
+        assert_eq!(
+            served_from,
+            Some(vec!["take_out", "take_out", "dine_in", "dine_in"])
+        );
+        assert!(validate_fifo_orders(
+            vec![1, 2],
+            vec![1, 3],
+            vec![1, 3, 1, 2]
+        ));
+        assert!(!validate_fifo_orders(
+            vec![1, 2],
+            vec![1, 3],
+            vec![1, 2, 3, 1]
+        ));
+    }
+
+    #[test]
+    fn report_first_violation() {
+        let take_out: &[i32] = &[1, 3, 5];
+        let dine_in: &[i32] = &[2, 4, 6];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        let report = validate_fifo_report(&queues, &[1, 2, 4, 6, 5, 3], false);
+        assert!(!report.is_valid());
+        assert_eq!(report.first_violation, Some(4));
+        assert_eq!(
+            report.expected_heads,
+            vec![("take_out", Some(3)), ("dine_in", None)]
+        );
+        assert!(report.served_from.is_empty());
+        assert!(report.unknown_ids.is_empty());
+    }
+
+    #[test]
+    fn report_unknown_ids() {
+        let take_out: &[i32] = &[1, 3];
+        let dine_in: &[i32] = &[2];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        let report = validate_fifo_report(&queues, &[1, 9, 2, 3], false);
+        assert_eq!(report.first_violation, Some(1));
+        assert_eq!(report.unknown_ids, vec![(1, 9)]);
+    }
+
+    #[test]
+    fn strict_report_unserved() {
+        let take_out: &[i32] = &[1, 3, 5];
+        let dine_in: &[i32] = &[2, 4];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        let lenient = validate_fifo_report(&queues, &[1, 2, 3], false);
+        assert!(lenient.is_valid());
+        assert_eq!(lenient.served_from, vec!["take_out", "dine_in", "take_out"]);
+
+        let strict = validate_fifo_report(&queues, &[1, 2, 3], true);
+        assert!(!strict.is_valid());
+        assert_eq!(strict.first_violation, None);
+        assert_eq!(
+            strict.unserved,
+            vec![("take_out", vec![5]), ("dine_in", vec![4])]
+        );
+
+        let complete = validate_fifo_report(&queues, &[1, 2, 3, 4, 5], true);
+        assert!(complete.is_valid());
+    }
+
+    #[test]
+    fn strict_report_order_served_once() {
+        let take_out: &[i32] = &[1];
+        let dine_in: &[i32] = &[1];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        let strict = validate_fifo_report(&queues, &[1], true);
+        assert!(!strict.is_valid());
+        assert_eq!(strict.unserved, vec![("dine_in", vec![1])]);
+    }
+
+    #[test]
+    fn monitor_flags_violation_when_served() {
+        let mut monitor = FifoMonitor::new();
+        monitor.order_placed("drive_thru", "a");
+        monitor.order_placed("delivery", "b");
+        monitor.order_placed("drive_thru", "c");
+
+        assert_eq!(monitor.order_served("delivery", "b"), None);
+        assert_eq!(
+            monitor.order_served("drive_thru", "c"),
+            Some(FifoViolation::OutOfOrder {
+                queue: "drive_thru".to_string(),
+                id: "c",
+                expected: "a",
+            })
+        );
+        // The out of order serve is still removed from the queue.
+        assert_eq!(monitor.pending("drive_thru"), vec!["a"]);
+        assert_eq!(monitor.order_served("drive_thru", "a"), None);
+    }
+
+    #[test]
+    fn monitor_flags_unknown_orders() {
+        let mut monitor = FifoMonitor::new();
+        monitor.order_placed("take_out", 1);
+
+        assert_eq!(
+            monitor.order_served("dine_in", 1),
+            Some(FifoViolation::Unknown {
+                queue: "dine_in".to_string(),
+                id: 1,
+            })
+        );
+        assert_eq!(monitor.order_served("take_out", 1), None);
+        assert_eq!(
+            monitor.order_served("take_out", 1),
+            Some(FifoViolation::Unknown {
+                queue: "take_out".to_string(),
+                id: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn monitor_handles_event_stream() {
+        let events = vec![
+            OrderEvent::Placed {
+                queue: "mobile_pickup".to_string(),
+                id: 7,
+            },
+            OrderEvent::Placed {
+                queue: "mobile_pickup".to_string(),
+                id: 8,
+            },
+            OrderEvent::Served {
+                queue: "mobile_pickup".to_string(),
+                id: 7,
+            },
+            OrderEvent::Placed {
+                queue: "mobile_pickup".to_string(),
+                id: 9,
+            },
+            OrderEvent::Served {
+                queue: "mobile_pickup".to_string(),
+                id: 9,
+            },
+        ];
+
+        let mut monitor = FifoMonitor::new();
+        let violations: Vec<_> = events
+            .into_iter()
+            .filter_map(|event| monitor.handle(event))
+            .collect();
+        assert_eq!(
+            violations,
+            vec![FifoViolation::OutOfOrder {
+                queue: "mobile_pickup".to_string(),
+                id: 9,
+                expected: 8,
+            }]
+        );
+    }
+
+    #[test]
+    fn count_matches_enumeration() {
+        let take_out: &[i32] = &[1, 3, 5];
+        let dine_in: &[i32] = &[2, 4, 6];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        let interleavings: Vec<Vec<i32>> = fifo_assignments(&queues).collect();
+        assert_eq!(count_fifo_assignments(&queues), BigUint::from(20));
+        assert_eq!(interleavings.len(), 20);
+        assert_eq!(interleavings[0], vec![1, 3, 5, 2, 4, 6]);
+        assert_eq!(interleavings[19], vec![2, 4, 6, 1, 3, 5]);
+
+        let distinct: HashSet<&Vec<i32>> = interleavings.iter().collect();
+        assert_eq!(distinct.len(), 20);
+        for served in &interleavings {
+            assert!(validate_fifo_report(&queues, served, true).is_valid());
+        }
+    }
+
+    #[test]
+    fn repeated_ids_repeat_sequences() {
+        let take_out: &[i32] = &[1, 2];
+        let dine_in: &[i32] = &[1, 3];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        // Six assignments, but both queues start with 1 so only four distinct sequences.
+        let sequences: Vec<Vec<i32>> = fifo_assignments(&queues).collect();
+        assert_eq!(count_fifo_assignments(&queues), BigUint::from(6));
+        assert_eq!(sequences.len(), 6);
+        let distinct: HashSet<&Vec<i32>> = sequences.iter().collect();
+        assert_eq!(distinct.len(), 4);
+        for served in &sequences {
+            assert!(validate_fifo_queues(&queues, served));
+        }
+
+        // [1, 1, 2, 3] and [1, 1, 3, 2] each come from two assignments, so they're sampled twice
+        // as often as the other two sequences.
+        let mut sampler = FifoAssignmentSampler::new(&queues, 32);
+        let mut counts: HashMap<Vec<i32>, u32> = HashMap::new();
+        for _ in 0..6000 {
+            *counts.entry(sampler.sample()).or_default() += 1;
+        }
+        assert_eq!(counts.len(), 4);
+        for (served, &count) in &counts {
+            let expected = match served[..] {
+                [1, 1, _, _] => 2000,
+                _ => 1000,
+            };
+            assert!(
+                count > expected * 85 / 100 && count < expected * 115 / 100,
+                "{:?} sampled {} times",
+                served,
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn count_with_empty_queues() {
+        let empty: &[i32] = &[];
+        let queues = [("take_out", empty), ("dine_in", empty)];
+        assert_eq!(count_fifo_assignments(&queues), BigUint::one());
+        assert_eq!(fifo_assignments(&queues).count(), 1);
+    }
+
+    #[test]
+    fn count_large_queues() {
+        let orders: Vec<i32> = (0..40).collect();
+        let queues = [
+            ("take_out", &orders[..]),
+            ("dine_in", &orders[..]),
+            ("drive_thru", &orders[..]),
+        ];
+
+        // Count paths through the grid of queue positions, one step per served order.
+        let mut ways = vec![vec![vec![BigUint::zero(); 41]; 41]; 41];
+        ways[0][0][0] = BigUint::one();
+        for i in 0..=40 {
+            for j in 0..=40 {
+                for k in 0..=40 {
+                    let mut total = ways[i][j][k].clone();
+                    if i > 0 {
+                        total = &total + &ways[i - 1][j][k];
+                    }
+                    if j > 0 {
+                        total = &total + &ways[i][j - 1][k];
+                    }
+                    if k > 0 {
+                        total = &total + &ways[i][j][k - 1];
+                    }
+                    ways[i][j][k] = total;
+                }
+            }
+        }
+
+        let count = count_fifo_assignments(&queues);
+        assert_eq!(count, ways[40][40][40]);
+        assert_eq!(
+            count.to_string(),
+            "12315686996104586105755778762527877375925475388598463020"
+        );
+    }
+
+    #[test]
+    fn sampler_is_seeded_and_uniform() {
+        let take_out: &[i32] = &[1, 3];
+        let dine_in: &[i32] = &[2, 4];
+        let queues = [("take_out", take_out), ("dine_in", dine_in)];
+
+        let mut sampler = FifoAssignmentSampler::new(&queues, 42);
+        let mut counts: HashMap<Vec<i32>, u32> = HashMap::new();
+        for _ in 0..6000 {
+            let served = sampler.sample();
+            assert!(validate_fifo_report(&queues, &served, true).is_valid());
+            *counts.entry(served).or_default() += 1;
+        }
+        assert_eq!(counts.len(), 6);
+        for &count in counts.values() {
+            assert!(count > 850 && count < 1150, "count {}", count);
+        }
+
+        let mut first = FifoAssignmentSampler::new(&queues, 7);
+        let mut second = FifoAssignmentSampler::new(&queues, 7);
+        for _ in 0..10 {
+            assert_eq!(first.sample(), second.sample());
+        }
+    }
 }
@@ -98,36 +98,218 @@ Space complexity: O(N)
     - Need O(N) for the cache.
     - Need O(N) for the stack size (worst case we remove 1 from bag size).
 */
+use crate::rational::Rational;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::Add;
+
+/// Value of a cake. Floats are convenient, but they can round differently depending on the order
+/// cakes are added up and f32 can't represent cents above ~16 million. Integers (e.g. cents) or
+/// Rational give exact answers.
+pub trait CakeValue: Copy + PartialOrd + Add<Output = Self> + Debug {
+    fn zero() -> Self;
+
+    /// Value of count cakes of this value.
+    fn times(self, count: u32) -> Self;
+
+    /// Value of numerator / denominator of a cake of this value. Integers round up (for positive
+    /// values).
+    fn fraction_of(self, numerator: u32, denominator: u32) -> Self;
+
+    /// Max value of a bag with a cake that has value and no weight, None if the type can't
+    /// represent it.
+    fn infinity() -> Option<Self>;
+
+    /// Whether two values computed in different ways are the same. Exact for integers and
+    /// Rational, within rounding error for floats.
+    fn is_close(self, other: Self) -> bool;
+}
+
+macro_rules! impl_cake_value_for_float {
+    ($float:ty) => {
+        impl CakeValue for $float {
+            fn zero() -> Self {
+                0.0
+            }
+
+            fn times(self, count: u32) -> Self {
+                self * count as $float
+            }
+
+            fn fraction_of(self, numerator: u32, denominator: u32) -> Self {
+                self * numerator as $float / denominator as $float
+            }
+
+            fn infinity() -> Option<Self> {
+                Some(<$float>::INFINITY)
+            }
+
+            fn is_close(self, other: Self) -> bool {
+                // Allow rounding error relative to the size of the values.
+                let tolerance = 1024.0 * <$float>::EPSILON * self.abs().max(other.abs());
+                self == other || (self - other).abs() <= tolerance
+            }
+        }
+    };
+}
+
+macro_rules! impl_cake_value_for_integer {
+    ($integer:ty) => {
+        impl CakeValue for $integer {
+            fn zero() -> Self {
+                0
+            }
+
+            fn times(self, count: u32) -> Self {
+                self * count as $integer
+            }
+
+            fn fraction_of(self, numerator: u32, denominator: u32) -> Self {
+                let scaled = self * numerator as $integer;
+                let denominator = denominator as $integer;
+                let rounding = if scaled % denominator == 0 { 0 } else { 1 };
+                scaled / denominator + rounding
+            }
+
+            fn infinity() -> Option<Self> {
+                None
+            }
+
+            fn is_close(self, other: Self) -> bool {
+                self == other
+            }
+        }
+    };
+}
+
+impl_cake_value_for_float!(f32);
+impl_cake_value_for_float!(f64);
+impl_cake_value_for_integer!(u32);
+impl_cake_value_for_integer!(u64);
+impl_cake_value_for_integer!(i32);
+impl_cake_value_for_integer!(i64);
+
+impl CakeValue for Rational {
+    fn zero() -> Self {
+        Rational::from_integer(0)
+    }
+
+    fn times(self, count: u32) -> Self {
+        self * Rational::from_integer(count as i64)
+    }
+
+    fn fraction_of(self, numerator: u32, denominator: u32) -> Self {
+        self * Rational::new(numerator as i64, denominator as i64)
+    }
+
+    fn infinity() -> Option<Self> {
+        None
+    }
+
+    fn is_close(self, other: Self) -> bool {
+        self == other
+    }
+}
 
 #[derive(Debug)]
-pub struct CakeType {
+pub struct CakeType<V = f32> {
     weight: u32,
-    value: f32,
+    value: V,
+    /// How many of this cake type are available, None if unlimited. Only the bounded solvers
+    /// look at this, the unbounded solvers always treat the amount as unlimited.
+    quantity: Option<u32>,
+    /// Resources used besides weight (e.g. volume), only looked at by
+    /// multi_constraint_bag_contents. Missing resources are treated as 0.
+    other_resources: Vec<u32>,
+}
+
+impl<V: CakeValue> CakeType<V> {
+    pub fn new(weight: u32, value: V) -> Self {
+        Self {
+            weight,
+            value,
+            quantity: None,
+            other_resources: vec![],
+        }
+    }
+
+    pub fn with_quantity(weight: u32, value: V, quantity: u32) -> Self {
+        Self {
+            weight,
+            value,
+            quantity: Some(quantity),
+            other_resources: vec![],
+        }
+    }
+
+    /// resources[0] is the weight, the rest are the other resources (e.g. volume) used by the
+    /// cake.
+    pub fn with_resources(resources: &[u32], value: V) -> Self {
+        let (&weight, other_resources) = resources.split_first().expect("cake needs a weight");
+        Self {
+            weight,
+            value,
+            quantity: None,
+            other_resources: other_resources.to_vec(),
+        }
+    }
+
+    /// Resource 0 is the weight.
+    fn resource(&self, dimension: usize) -> u32 {
+        if dimension == 0 {
+            return self.weight;
+        }
+        self.other_resources
+            .get(dimension - 1)
+            .cloned()
+            .unwrap_or(0)
+    }
 }
 
-pub fn max_bag_value(cake_types: &[CakeType], bag_size: u32) -> f32 {
+/// Panics if the max value is infinite and the value type has no infinity, use
+/// checked_max_bag_value for those.
+pub fn max_bag_value<V: CakeValue>(cake_types: &[CakeType<V>], bag_size: u32) -> V {
+    checked_max_bag_value(cake_types, bag_size).unwrap_or_else(|| {
+        V::infinity().expect("a cake with value and no weight needs a value type with infinity")
+    })
+}
+
+/// Returns None if the max value is infinite (a cake with value and no weight).
+pub fn checked_max_bag_value<V: CakeValue>(cake_types: &[CakeType<V>], bag_size: u32) -> Option<V> {
     let mut recursive_cache = HashMap::new();
     let recursive = recursive(cake_types, bag_size, &mut recursive_cache);
     let bottoms_up = bottoms_up(cake_types, bag_size);
-    assert_eq!(bottoms_up, recursive);
+    assert_eq!(bottoms_up.is_some(), recursive.is_some());
+    if let (Some(bottoms_up), Some(recursive)) = (bottoms_up, recursive) {
+        assert!(
+            bottoms_up.is_close(recursive),
+            "{:?} != {:?}",
+            bottoms_up,
+            recursive
+        );
+    }
     recursive
 }
 
-fn recursive(cake_types: &[CakeType], bag_size: u32, cache: &mut HashMap<u32, f32>) -> f32 {
+/// Returns None if the max value is infinite.
+fn recursive<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+    cache: &mut HashMap<u32, V>,
+) -> Option<V> {
     if cache.contains_key(&bag_size) {
-        return cache[&bag_size];
+        return Some(cache[&bag_size]);
     }
 
-    let mut max_value = 0.0;
+    let mut max_value = V::zero();
     for i in 0..cake_types.len() {
         let current_type = &cake_types[i];
         // Handle edge case where cakes can have no weight.
         if current_type.weight == 0 {
-            if current_type.value > 0.0 {
-                // Set max value to infinity if a cake has value and no weight.
-                max_value = std::f32::INFINITY;
-                break;
+            if current_type.value > V::zero() {
+                // Max value is infinite if a cake has value and no weight.
+                return None;
             } else {
                 // Ignore cakes that have no weight + no value / negative value.
                 continue;
@@ -139,13 +321,13 @@ fn recursive(cake_types: &[CakeType], bag_size: u32, cache: &mut HashMap<u32, f3
         }
 
         let new_size = bag_size - current_type.weight;
-        let inner_max_value = recursive(cake_types, new_size, cache) + current_type.value;
+        let inner_max_value = recursive(cake_types, new_size, cache)? + current_type.value;
         if inner_max_value > max_value {
             max_value = inner_max_value;
         }
     }
     cache.insert(bag_size, max_value);
-    max_value
+    Some(max_value)
 }
 
 /*
@@ -153,15 +335,33 @@ For a bottoms up solution, we can create an array of size N which stores max_bag
 at each index for that bag size. We can go through each index and calculate the max_bag_value
 by going through each of the cake types.
 */
-fn bottoms_up(cake_types: &[CakeType], bag_size: u32) -> f32 {
+fn bottoms_up<V: CakeValue>(cake_types: &[CakeType<V>], bag_size: u32) -> Option<V> {
+    let max_bag_values = bottoms_up_table(cake_types, bag_size)?.max_bag_values;
+    Some(max_bag_values[bag_size as usize])
+}
+
+struct BottomsUpTable<V> {
+    max_bag_values: Vec<V>,
+    /// Index of the cake type last added to reach max_bag_values at each bag size, None if the
+    /// best option is to take nothing.
+    last_cake_types: Vec<Option<usize>>,
+}
+
+/// Returns None if the max value is infinite (a cake with value and no weight).
+fn bottoms_up_table<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> Option<BottomsUpTable<V>> {
     let mut max_bag_values = vec![];
+    let mut last_cake_types = vec![];
     for curr_bag_size in 0..=bag_size {
-        let mut max_bag_value = 0.0;
-        for cake_type in cake_types {
-            // Handle cakes with no weight edge case (if value, return INFINITY, otherwise ignore cake).
+        let mut max_bag_value = V::zero();
+        let mut last_cake_type = None;
+        for (index, cake_type) in cake_types.iter().enumerate() {
+            // Handle cakes with no weight edge case (if value, it's infinite, otherwise ignore cake).
             if cake_type.weight == 0 {
-                if cake_type.value > 0.0 {
-                    return std::f32::INFINITY;
+                if cake_type.value > V::zero() {
+                    return None;
                 } else {
                     continue;
                 }
@@ -176,34 +376,555 @@ fn bottoms_up(cake_types: &[CakeType], bag_size: u32) -> f32 {
                 max_bag_values[(curr_bag_size - cake_type.weight) as usize] + cake_type.value;
             if other_bag_value > max_bag_value {
                 max_bag_value = other_bag_value;
+                last_cake_type = Some(index);
+            }
+        }
+        max_bag_values.push(max_bag_value);
+        last_cake_types.push(last_cake_type);
+    }
+
+    Some(BottomsUpTable {
+        max_bag_values,
+        last_cake_types,
+    })
+}
+
+/// How many of each cake type to take, along with the totals for the bag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BagContents<V = f32> {
+    /// Indexed the same as the cake types given.
+    pub counts: Vec<u32>,
+    pub total_weight: u32,
+    pub total_value: V,
+}
+
+/*
+To find what's in the bag, walk back through the bottoms up table: the cake type recorded for
+a bag size is the last cake added to get its max value, so take it and continue from the bag size
+without it.
+
+Time complexity: O(N * M), same as bottoms_up, the walk back is O(N).
+Space complexity: O(N)
+*/
+/// Returns None if the max value is infinite, in which case there is no finite set of cakes.
+pub fn optimal_bag_contents<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> Option<BagContents<V>> {
+    let table = bottoms_up_table(cake_types, bag_size)?;
+
+    let mut counts = vec![0; cake_types.len()];
+    let mut total_weight = 0;
+    let mut remaining_size = bag_size;
+    while let Some(index) = table.last_cake_types[remaining_size as usize] {
+        counts[index] += 1;
+        total_weight += cake_types[index].weight;
+        remaining_size -= cake_types[index].weight;
+    }
+
+    Some(BagContents {
+        counts,
+        total_weight,
+        total_value: table.max_bag_values[bag_size as usize],
+    })
+}
+
+/*
+Bounded variants:
+When there's a limited stock of each cake type, the bottoms up table can't just look at every
+cake type for every bag size, since it doesn't know how many of a cake are already in the bag.
+
+Instead, treat every cake as its own item that can be taken at most once (0/1 knapsack) and go
+through the items one at a time, updating the max value for every bag size. Bag sizes are updated
+from largest to smallest so an item added to a smaller bag size isn't counted again for a larger
+one.
+
+To avoid having one item per cake, a stock of Q cakes is split into items of 1, 2, 4, ... cakes
+and whatever is left over. Any amount from 0 to Q can be made from these, so only O(log Q) items
+are needed per cake type.
+
+Cakes without weight follow the same rules as above: if they have value all of them are taken
+(infinite value when the stock is unlimited), otherwise they're ignored.
+
+Time complexity: O(N * M * log Q) where N is the bag size, M is the number of cake types and Q is
+the largest stock of a cake type (capped at how many fit in the bag).
+Space complexity: O(N * M * log Q) to remember which items were taken at each bag size.
+*/
+/// Takes at most one of each cake type, ignoring quantity.
+pub fn zero_one_bag_contents<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> BagContents<V> {
+    bounded_contents(cake_types, bag_size, |_cake_type| Some(1))
+        .expect("finite since every stock is limited")
+}
+
+/// Takes at most quantity of each cake type. Returns None if the max value is infinite (a cake with
+/// value and no weight has an unlimited quantity).
+pub fn bounded_bag_contents<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> Option<BagContents<V>> {
+    bounded_contents(cake_types, bag_size, |cake_type| cake_type.quantity)
+}
+
+struct BoundedItem<V> {
+    cake_type_index: usize,
+    count: u32,
+    weight: u32,
+    value: V,
+}
+
+fn bounded_contents<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+    quantity: impl Fn(&CakeType<V>) -> Option<u32>,
+) -> Option<BagContents<V>> {
+    let mut counts = vec![0; cake_types.len()];
+    let mut weightless_value = V::zero();
+
+    let mut items = vec![];
+    for (index, cake_type) in cake_types.iter().enumerate() {
+        if cake_type.weight == 0 {
+            if cake_type.value > V::zero() {
+                // Take every cake with value and no weight.
+                let quantity = quantity(cake_type)?;
+                counts[index] += quantity;
+                weightless_value = weightless_value + cake_type.value.times(quantity);
+            }
+            continue;
+        }
+
+        let fits_in_bag = bag_size / cake_type.weight;
+        let mut remaining = quantity(cake_type).map_or(fits_in_bag, |q| q.min(fits_in_bag));
+        let mut count = 1;
+        while remaining > 0 {
+            let count_taken = count.min(remaining);
+            items.push(BoundedItem {
+                cake_type_index: index,
+                count: count_taken,
+                weight: count_taken * cake_type.weight,
+                value: cake_type.value.times(count_taken),
+            });
+            remaining -= count_taken;
+            count *= 2;
+        }
+    }
+
+    let mut max_bag_values = vec![V::zero(); bag_size as usize + 1];
+    let mut taken = vec![vec![false; bag_size as usize + 1]; items.len()];
+    for (item_index, item) in items.iter().enumerate() {
+        for curr_bag_size in (item.weight..=bag_size).rev() {
+            let other_bag_value =
+                max_bag_values[(curr_bag_size - item.weight) as usize] + item.value;
+            if other_bag_value > max_bag_values[curr_bag_size as usize] {
+                max_bag_values[curr_bag_size as usize] = other_bag_value;
+                taken[item_index][curr_bag_size as usize] = true;
+            }
+        }
+    }
+
+    // Walk back through the items, an item taken at the remaining bag size is in the bag.
+    let mut total_weight = 0;
+    let mut remaining_size = bag_size;
+    for (item_index, item) in items.iter().enumerate().rev() {
+        if taken[item_index][remaining_size as usize] {
+            counts[item.cake_type_index] += item.count;
+            total_weight += item.weight;
+            remaining_size -= item.weight;
+        }
+    }
+
+    Some(BagContents {
+        counts,
+        total_weight,
+        total_value: max_bag_values[bag_size as usize] + weightless_value,
+    })
+}
+
+/*
+Huge bag sizes:
+The solutions above need O(N) memory for a bag of size N, which isn't possible for bag sizes near
+u32::MAX. When there are only a few cake types, branch and bound works without a table.
+
+Going back to the (value / weight) ordering from the notes: greedy doesn't always give the best
+answer, but it gives an upper bound. If the rest of the bag were filled with (fractional) cakes of
+the best remaining ratio, no real choice of cakes could do better.
+
+So try cake types in ratio order, taking as many of the current cake as fit first and then one
+less each time. Whenever the upper bound for the rest of the bag can't beat the best bag found so
+far, stop. Taking one less of the current cake only frees up space for cakes with a worse ratio,
+so the bound only goes down and the rest of the counts for this cake can be skipped too.
+
+Ratios are compared by cross multiplying (v1 * w2 > v2 * w1) so no division is needed, which means
+integer values need room for value * bag size without overflowing.
+
+Time complexity: exponential in the number of cake types in the worst case, but the bound prunes
+almost everything in practice. Doesn't depend on the bag size directly.
+Space complexity: O(M) where M is the number of cake types.
+*/
+/// Returns None if the max value is infinite (a cake with value and no weight).
+pub fn branch_and_bound_bag_contents<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> Option<BagContents<V>> {
+    let mut order = vec![];
+    for (index, cake_type) in cake_types.iter().enumerate() {
+        if cake_type.value <= V::zero() {
+            // Cakes without value never make the bag better.
+            continue;
+        }
+        if cake_type.weight == 0 {
+            // Max value is infinite if a cake has value and no weight.
+            return None;
+        }
+        order.push(index);
+    }
+
+    sort_by_ratio(cake_types, &mut order);
+
+    let mut search = BranchAndBound {
+        cake_types,
+        order,
+        counts: vec![0; cake_types.len()],
+        best_value: V::zero(),
+        best_counts: vec![0; cake_types.len()],
+    };
+    search.search(0, bag_size, V::zero());
+
+    let total_weight = search
+        .best_counts
+        .iter()
+        .zip(cake_types)
+        .map(|(count, cake_type)| count * cake_type.weight)
+        .sum();
+    Some(BagContents {
+        counts: search.best_counts,
+        total_weight,
+        total_value: search.best_value,
+    })
+}
+
+/// Best (value / weight) ratio first. Cakes need weight.
+fn sort_by_ratio<V: CakeValue>(cake_types: &[CakeType<V>], order: &mut [usize]) {
+    order.sort_by(|&a, &b| {
+        let a_scaled = cake_types[a].value.times(cake_types[b].weight);
+        let b_scaled = cake_types[b].value.times(cake_types[a].weight);
+        b_scaled.partial_cmp(&a_scaled).unwrap_or(Ordering::Equal)
+    });
+}
+
+struct BranchAndBound<'a, V> {
+    cake_types: &'a [CakeType<V>],
+    /// Indices of the cake types worth taking, best ratio first.
+    order: Vec<usize>,
+    counts: Vec<u32>,
+    best_value: V,
+    best_counts: Vec<u32>,
+}
+
+impl<'a, V: CakeValue> BranchAndBound<'a, V> {
+    fn search(&mut self, depth: usize, remaining_size: u32, value: V) {
+        if value > self.best_value {
+            self.best_value = value;
+            self.best_counts = self.counts.clone();
+        }
+
+        let index = match self.order.get(depth) {
+            Some(&index) => index,
+            None => return,
+        };
+        let cake_type = &self.cake_types[index];
+        let max_count = remaining_size / cake_type.weight;
+
+        let next_cake_type = match self.order.get(depth + 1) {
+            Some(&next_index) => &self.cake_types[next_index],
+            None => {
+                // Last cake type, so there's nothing better to do with the space than fill it.
+                self.counts[index] = max_count;
+                self.search(depth + 1, 0, value + cake_type.value.times(max_count));
+                self.counts[index] = 0;
+                return;
+            }
+        };
+
+        for count in (0..=max_count).rev() {
+            let remaining_size = remaining_size - count * cake_type.weight;
+            let value = value + cake_type.value.times(count);
+
+            // Upper bound is value + remaining_size * next ratio, scaled by the next weight.
+            let upper_bound =
+                value.times(next_cake_type.weight) + next_cake_type.value.times(remaining_size);
+            if upper_bound <= self.best_value.times(next_cake_type.weight) {
+                break;
+            }
+
+            self.counts[index] = count;
+            self.search(depth + 1, remaining_size, value);
+        }
+        self.counts[index] = 0;
+    }
+}
+
+/*
+Multiple constraints:
+When a bag has a limit on more than just weight (e.g. volume), the bottoms up table gets one
+dimension per limit. Each entry is the max value for a bag with those limits, and is calculated
+the same way as before: for every cake that fits in all dimensions, look up the entry for the
+limits left over after adding the cake.
+
+The table is stored flat, so a bag with limits (c_0, ..., c_d) is at
+c_0 + c_1 * (C_0 + 1) + c_2 * (C_0 + 1) * (C_1 + 1) + ...
+Taking a cake that uses some resource always moves to a smaller index, so going through the
+table in index order means every entry needed has already been calculated.
+
+With a single limit this is exactly bottoms_up_table.
+
+Time complexity: O(P * M * D) where P is the product of (capacity + 1) over all dimensions, M is
+the number of cake types and D is the number of dimensions.
+Space complexity: O(P)
+*/
+/// capacities[0] is the bag size (weight limit), the rest are limits on the other resources of
+/// the cakes in the same order. Returns None if the max value is infinite (a cake with value that
+/// uses no resources at all).
+pub fn multi_constraint_bag_contents<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    capacities: &[u32],
+) -> Option<BagContents<V>> {
+    assert!(!capacities.is_empty(), "bag needs a weight limit");
+
+    // How far apart entries are in the flat table for a step of 1 in each dimension.
+    let mut strides = Vec::with_capacity(capacities.len());
+    let mut table_size = 1;
+    for &capacity in capacities {
+        strides.push(table_size);
+        table_size *= capacity as usize + 1;
+    }
+
+    let mut max_bag_values: Vec<V> = Vec::with_capacity(table_size);
+    let mut last_cake_types: Vec<Option<usize>> = Vec::with_capacity(table_size);
+    let mut limits = vec![0; capacities.len()];
+    for table_index in 0..table_size {
+        // Limits for this entry, counting up in each dimension like an odometer.
+        if table_index > 0 {
+            for (limit, &capacity) in limits.iter_mut().zip(capacities) {
+                if *limit < capacity {
+                    *limit += 1;
+                    break;
+                }
+                *limit = 0;
+            }
+        }
+
+        let mut max_bag_value = V::zero();
+        let mut last_cake_type = None;
+        for (index, cake_type) in cake_types.iter().enumerate() {
+            let resources = (0..capacities.len()).map(|dimension| cake_type.resource(dimension));
+
+            // Handle cakes that use no resources edge case (if value, it's infinite, otherwise
+            // ignore cake).
+            if resources.clone().all(|resource| resource == 0) {
+                if cake_type.value > V::zero() {
+                    return None;
+                } else {
+                    continue;
+                }
+            }
+
+            // Can't hold cake in the current bag limits.
+            if resources
+                .clone()
+                .zip(&limits)
+                .any(|(resource, &limit)| resource > limit)
+            {
+                continue;
+            }
+
+            let offset: usize = resources
+                .zip(&strides)
+                .map(|(resource, &stride)| resource as usize * stride)
+                .sum();
+            let other_bag_value = max_bag_values[table_index - offset] + cake_type.value;
+            if other_bag_value > max_bag_value {
+                max_bag_value = other_bag_value;
+                last_cake_type = Some(index);
             }
         }
         max_bag_values.push(max_bag_value);
+        last_cake_types.push(last_cake_type);
+    }
+
+    // Walk back through the table the same way as optimal_bag_contents.
+    let mut counts = vec![0; cake_types.len()];
+    let mut total_weight = 0;
+    let mut table_index = table_size - 1;
+    while let Some(index) = last_cake_types[table_index] {
+        counts[index] += 1;
+        total_weight += cake_types[index].weight;
+        table_index -= (0..capacities.len())
+            .map(|dimension| cake_types[index].resource(dimension) as usize * strides[dimension])
+            .sum::<usize>();
+    }
+
+    Some(BagContents {
+        counts,
+        total_weight,
+        total_value: max_bag_values[table_size - 1],
+    })
+}
+
+/*
+Greedy vs optimal:
+As the notes show, taking cakes by (value / weight) ratio isn't optimal when cakes can't be cut.
+If they could be cut, it would be: fill the bag with the best ratio cake first, and when only part
+of the next cake fits, take that part. Nothing can beat that, so it's an upper bound for the
+whole-cake answer.
+
+greedy_gap_report puts the three side by side: the whole-cake greedy answer, the optimal answer
+and the fractional upper bound. Both greedy solvers respect quantity.
+
+Time complexity: O(M log M) for the greedy solvers, the report also runs bounded_bag_contents.
+Space complexity: O(M)
+*/
+/// Cakes that can be cut, see fractional_bag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FractionalBag<V = f32> {
+    /// How much of each cake type is in the bag, measured in weight (so the fraction of a cake
+    /// taken is weight_taken / weight). Indexed the same as the cake types given. Cakes without
+    /// weight are always taken in full and only show up in total_value.
+    pub weights_taken: Vec<u32>,
+    pub total_weight: u32,
+    /// Integer values are rounded up for partial cakes, so this is still an upper bound.
+    pub total_value: V,
+}
+
+/// Returns None if the max value is infinite (a cake with value and no weight has an unlimited
+/// quantity).
+pub fn fractional_bag<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> Option<FractionalBag<V>> {
+    let mut weights_taken = vec![0; cake_types.len()];
+    let mut total_weight = 0;
+    let mut total_value = V::zero();
+    let mut remaining_size = bag_size;
+
+    for index in greedy_order(cake_types)? {
+        let cake_type = &cake_types[index];
+        if cake_type.weight == 0 {
+            // Only cakes with value and a limited quantity are left in the order.
+            let quantity = cake_type.quantity.expect("limited");
+            total_value = total_value + cake_type.value.times(quantity);
+            continue;
+        }
+
+        let available = cake_type.quantity.map_or(u32::MAX, |quantity| {
+            quantity.saturating_mul(cake_type.weight)
+        });
+        let weight_taken = available.min(remaining_size);
+        weights_taken[index] = weight_taken;
+        total_weight += weight_taken;
+        total_value = total_value + cake_type.value.fraction_of(weight_taken, cake_type.weight);
+        remaining_size -= weight_taken;
+    }
+
+    Some(FractionalBag {
+        weights_taken,
+        total_weight,
+        total_value,
+    })
+}
+
+/// Takes as many whole cakes as fit, best (value / weight) ratio first. Returns None if the max
+/// value is infinite.
+pub fn greedy_bag_contents<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> Option<BagContents<V>> {
+    let mut counts = vec![0; cake_types.len()];
+    let mut total_weight = 0;
+    let mut total_value = V::zero();
+    let mut remaining_size = bag_size;
+
+    for index in greedy_order(cake_types)? {
+        let cake_type = &cake_types[index];
+        // Only cakes with value and a limited quantity can have no weight here.
+        let fits_in_bag = remaining_size.checked_div(cake_type.weight);
+        let count = match (cake_type.quantity, fits_in_bag) {
+            (Some(quantity), Some(fits_in_bag)) => quantity.min(fits_in_bag),
+            (Some(quantity), None) => quantity,
+            (None, Some(fits_in_bag)) => fits_in_bag,
+            (None, None) => unreachable!("unlimited cakes without weight are infinite"),
+        };
+
+        counts[index] = count;
+        total_weight += count * cake_type.weight;
+        total_value = total_value + cake_type.value.times(count);
+        remaining_size -= count * cake_type.weight;
     }
 
-    dbg!(&max_bag_values);
-    max_bag_values[bag_size as usize]
+    Some(BagContents {
+        counts,
+        total_weight,
+        total_value,
+    })
+}
+
+/// Indices of the cake types with value, best ratio first (cakes without weight before all
+/// others). None if a cake with value and no weight has an unlimited quantity.
+fn greedy_order<V: CakeValue>(cake_types: &[CakeType<V>]) -> Option<Vec<usize>> {
+    let mut order = vec![];
+    for (index, cake_type) in cake_types.iter().enumerate() {
+        if cake_type.value <= V::zero() {
+            continue;
+        }
+        if cake_type.weight == 0 && cake_type.quantity.is_none() {
+            return None;
+        }
+        order.push(index);
+    }
+
+    // Cakes without weight have an infinite ratio, weight 0 sorts them first.
+    order.sort_by_key(|&index| cake_types[index].weight != 0);
+    let first_weighted = order
+        .iter()
+        .position(|&index| cake_types[index].weight != 0)
+        .unwrap_or(order.len());
+    sort_by_ratio(cake_types, &mut order[first_weighted..]);
+    Some(order)
+}
+
+/// Compares the greedy answer to the optimal answer and the fractional upper bound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreedyGapReport<V = f32> {
+    pub greedy: BagContents<V>,
+    pub optimal: BagContents<V>,
+    pub fractional: FractionalBag<V>,
+}
+
+/// Returns None if the max value is infinite.
+pub fn greedy_gap_report<V: CakeValue>(
+    cake_types: &[CakeType<V>],
+    bag_size: u32,
+) -> Option<GreedyGapReport<V>> {
+    Some(GreedyGapReport {
+        greedy: greedy_bag_contents(cake_types, bag_size)?,
+        optimal: bounded_bag_contents(cake_types, bag_size)?,
+        fractional: fractional_bag(cake_types, bag_size)?,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::split_mix::SplitMix64;
 
     #[test]
     fn works_for_example() {
         let cake_types = vec![
-            CakeType {
-                weight: 7,
-                value: 160.0,
-            },
-            CakeType {
-                weight: 3,
-                value: 90.0,
-            },
-            CakeType {
-                weight: 2,
-                value: 15.0,
-            },
+            CakeType::new(7, 160.0),
+            CakeType::new(3, 90.0),
+            CakeType::new(2, 15.0),
         ];
 
         // (6 of middle cake and 1 of last type of cake)
@@ -213,18 +934,9 @@ mod tests {
     #[test]
     fn works_when_greedy_approach_fails() {
         let cake_types = vec![
-            CakeType {
-                weight: 3,
-                value: 6.0,
-            },
-            CakeType {
-                weight: 2,
-                value: 3.9,
-            },
-            CakeType {
-                weight: 1,
-                value: 1.0,
-            },
+            CakeType::new(3, 6.0),
+            CakeType::new(2, 3.9),
+            CakeType::new(1, 1.0),
         ];
 
         // 2 of the middle cake
@@ -233,33 +945,320 @@ mod tests {
 
     #[test]
     fn works_when_cakes_have_weight_of_zero() {
-        let cake_types = vec![
-            CakeType {
-                weight: 0,
-                value: 6.0,
-            },
-            CakeType {
-                weight: 1,
-                value: 1.0,
-            },
-        ];
+        let cake_types = vec![CakeType::new(0, 6.0), CakeType::new(1, 1.0)];
 
         assert_eq!(max_bag_value(&cake_types, 4), std::f32::INFINITY);
     }
 
     #[test]
     fn works_when_cakes_have_weight_of_zero_and_value_of_zero() {
+        let cake_types = vec![CakeType::new(1, 1.0), CakeType::new(0, 0.0)];
+
+        assert_eq!(max_bag_value(&cake_types, 4), 4.0);
+    }
+
+    #[test]
+    fn contents_for_example() {
         let cake_types = vec![
-            CakeType {
-                weight: 1,
-                value: 1.0,
-            },
-            CakeType {
-                weight: 0,
-                value: 0.0,
-            },
+            CakeType::new(7, 160.0),
+            CakeType::new(3, 90.0),
+            CakeType::new(2, 15.0),
         ];
 
-        assert_eq!(max_bag_value(&cake_types, 4), 4.0);
+        let contents = optimal_bag_contents(&cake_types, 20).expect("finite");
+        assert_eq!(contents.counts, vec![0, 6, 1]);
+        assert_eq!(contents.total_weight, 20);
+        assert_eq!(contents.total_value, max_bag_value(&cake_types, 20));
+    }
+
+    #[test]
+    fn contents_when_greedy_approach_fails() {
+        let cake_types = vec![
+            CakeType::new(3, 6.0),
+            CakeType::new(2, 3.9),
+            CakeType::new(1, 1.0),
+        ];
+
+        let contents = optimal_bag_contents(&cake_types, 4).expect("finite");
+        assert_eq!(contents.counts, vec![0, 2, 0]);
+        assert_eq!(contents.total_weight, 4);
+        assert_eq!(contents.total_value, max_bag_value(&cake_types, 4));
+    }
+
+    #[test]
+    fn contents_leave_unused_space() {
+        let cake_types = vec![CakeType::new(3, 5.0), CakeType::new(0, 0.0)];
+
+        let contents = optimal_bag_contents(&cake_types, 5).expect("finite");
+        assert_eq!(contents.counts, vec![1, 0]);
+        assert_eq!(contents.total_weight, 3);
+        assert_eq!(contents.total_value, 5.0);
+    }
+
+    #[test]
+    fn no_contents_when_value_is_infinite() {
+        let cake_types = vec![CakeType::new(0, 6.0), CakeType::new(1, 1.0)];
+        assert_eq!(optimal_bag_contents(&cake_types, 4), None);
+    }
+
+    #[test]
+    fn zero_one_takes_each_cake_once() {
+        let cake_types = vec![
+            CakeType::new(1, 1.0),
+            CakeType::new(3, 4.0),
+            CakeType::new(4, 5.0),
+            CakeType::new(5, 7.0),
+        ];
+
+        let contents = zero_one_bag_contents(&cake_types, 7);
+        assert_eq!(contents.counts, vec![0, 1, 1, 0]);
+        assert_eq!(contents.total_weight, 7);
+        assert_eq!(contents.total_value, 9.0);
+    }
+
+    #[test]
+    fn bounded_respects_quantity() {
+        let cake_types = vec![
+            CakeType::new(7, 160.0),
+            CakeType::with_quantity(3, 90.0, 2),
+            CakeType::new(2, 15.0),
+        ];
+
+        let contents = bounded_bag_contents(&cake_types, 20).expect("finite");
+        assert_eq!(contents.counts, vec![2, 2, 0]);
+        assert_eq!(contents.total_weight, 20);
+        assert_eq!(contents.total_value, 500.0);
+    }
+
+    #[test]
+    fn bounded_matches_unbounded_without_quantities() {
+        let cake_types = vec![
+            CakeType::new(7, 160.0),
+            CakeType::new(3, 90.0),
+            CakeType::new(2, 15.0),
+        ];
+
+        let contents = bounded_bag_contents(&cake_types, 20).expect("finite");
+        assert_eq!(contents.total_value, max_bag_value(&cake_types, 20));
+        assert_eq!(contents.counts, vec![0, 6, 1]);
+    }
+
+    #[test]
+    fn bounded_when_cakes_have_weight_of_zero() {
+        let cake_types = vec![
+            CakeType::with_quantity(0, 6.0, 3),
+            CakeType::with_quantity(0, 0.0, 3),
+            CakeType::new(1, 1.0),
+        ];
+
+        let contents = bounded_bag_contents(&cake_types, 4).expect("finite");
+        assert_eq!(contents.counts, vec![3, 0, 4]);
+        assert_eq!(contents.total_weight, 4);
+        assert_eq!(contents.total_value, 22.0);
+
+        let zero_one = zero_one_bag_contents(&cake_types, 4);
+        assert_eq!(zero_one.counts, vec![1, 0, 1]);
+        assert_eq!(zero_one.total_value, 7.0);
+
+        let unlimited = vec![CakeType::new(0, 6.0), CakeType::new(1, 1.0)];
+        assert_eq!(bounded_bag_contents(&unlimited, 4), None);
+    }
+
+    #[test]
+    fn exact_with_integer_cents() {
+        // f32 can't represent 16_777_217 cents exactly.
+        let cake_types = vec![
+            CakeType::new(3, 16_777_217u64),
+            CakeType::new(2, 11_000_001),
+        ];
+
+        assert_eq!(max_bag_value(&cake_types, 7), 38_777_219);
+        let contents = optimal_bag_contents(&cake_types, 7).expect("finite");
+        assert_eq!(contents.counts, vec![1, 2]);
+        assert_eq!(contents.total_value, 38_777_219);
+    }
+
+    #[test]
+    fn exact_with_rational() {
+        let cake_types = vec![
+            CakeType::new(3, Rational::new(6, 10)),
+            CakeType::new(2, Rational::new(39, 100)),
+            CakeType::new(1, Rational::new(1, 10)),
+        ];
+
+        assert_eq!(max_bag_value(&cake_types, 4), Rational::new(78, 100));
+        let contents = bounded_bag_contents(&cake_types, 4).expect("finite");
+        assert_eq!(contents.total_value, Rational::new(78, 100));
+    }
+
+    #[test]
+    fn works_with_f64() {
+        let cake_types = vec![CakeType::new(1, 0.1f64), CakeType::new(2, 0.2)];
+
+        // 0.1 + 0.2 isn't exactly 0.3 as a float, is_close allows for the rounding.
+        let value = max_bag_value(&cake_types, 3);
+        assert_ne!(value, 0.3);
+        assert!(value.is_close(0.3));
+    }
+
+    #[test]
+    fn infinite_value_without_infinity() {
+        let cake_types = vec![CakeType::new(0, 5u32), CakeType::new(1, 1)];
+        assert_eq!(checked_max_bag_value(&cake_types, 4), None);
+    }
+
+    #[test]
+    fn branch_and_bound_matches_bottoms_up() {
+        // Small deterministic pseudo-random instances.
+        let mut rng = SplitMix64::new(12345);
+        let mut next = |bound: u64| rng.next_below(bound);
+
+        for _ in 0..200 {
+            let cake_types: Vec<CakeType<u64>> = (0..1 + next(5))
+                .map(|_| CakeType::new(1 + next(20) as u32, next(100)))
+                .collect();
+            let bag_size = next(200) as u32;
+
+            let expected = optimal_bag_contents(&cake_types, bag_size).expect("finite");
+            let contents = branch_and_bound_bag_contents(&cake_types, bag_size).expect("finite");
+            assert_eq!(contents.total_value, expected.total_value);
+            assert!(contents.total_weight <= bag_size);
+
+            let counted_value: u64 = contents
+                .counts
+                .iter()
+                .zip(&cake_types)
+                .map(|(&count, cake_type)| cake_type.value.times(count))
+                .sum();
+            assert_eq!(counted_value, contents.total_value);
+        }
+    }
+
+    #[test]
+    fn branch_and_bound_with_huge_bag() {
+        let cake_types = vec![CakeType::new(7, 15u64), CakeType::new(3, 6)];
+        let bag_size = u32::MAX;
+
+        // Using the (3, 6) cake 7 or more times could be swapped for 3 of the (7, 15) cake, so the
+        // best bag uses it fewer than 7 times.
+        let expected = (0..7u64)
+            .map(|small| 6 * small + 15 * ((bag_size as u64 - 3 * small) / 7))
+            .max()
+            .expect("non-empty");
+
+        let contents = branch_and_bound_bag_contents(&cake_types, bag_size).expect("finite");
+        assert_eq!(contents.total_value, expected);
+    }
+
+    #[test]
+    fn branch_and_bound_edge_cases() {
+        let infinite = vec![CakeType::new(0, 6.0), CakeType::new(1, 1.0)];
+        assert_eq!(branch_and_bound_bag_contents(&infinite, 4), None);
+
+        let cake_types = vec![CakeType::new(1, 1.0), CakeType::new(0, 0.0)];
+        let contents = branch_and_bound_bag_contents(&cake_types, 4).expect("finite");
+        assert_eq!(contents.total_value, max_bag_value(&cake_types, 4));
+        assert_eq!(contents.counts, vec![4, 0]);
+    }
+
+    #[test]
+    fn multi_constraint_with_one_dimension_matches() {
+        let examples = vec![
+            (
+                vec![
+                    CakeType::new(7, 160.0),
+                    CakeType::new(3, 90.0),
+                    CakeType::new(2, 15.0),
+                ],
+                20,
+            ),
+            (
+                vec![
+                    CakeType::new(3, 6.0),
+                    CakeType::new(2, 3.9),
+                    CakeType::new(1, 1.0),
+                ],
+                4,
+            ),
+            (vec![CakeType::new(1, 1.0), CakeType::new(0, 0.0)], 4),
+        ];
+
+        for (cake_types, bag_size) in examples {
+            let contents = multi_constraint_bag_contents(&cake_types, &[bag_size]).expect("finite");
+            assert_eq!(contents.total_value, max_bag_value(&cake_types, bag_size));
+            assert_eq!(Some(contents), optimal_bag_contents(&cake_types, bag_size));
+        }
+    }
+
+    #[test]
+    fn multi_constraint_weight_and_volume() {
+        let cake_types = vec![
+            // Light but bulky.
+            CakeType::with_resources(&[1, 5], 10u32),
+            // Heavy but compact.
+            CakeType::with_resources(&[5, 1], 10),
+            CakeType::with_resources(&[3, 3], 7),
+        ];
+
+        // Weight alone would take 10 of the first cake, volume alone 10 of the second.
+        let contents = multi_constraint_bag_contents(&cake_types, &[10, 10]).expect("finite");
+        assert_eq!(contents.counts, vec![1, 1, 1]);
+        assert_eq!(contents.total_weight, 9);
+        assert_eq!(contents.total_value, 27);
+    }
+
+    #[test]
+    fn multi_constraint_weightless_cakes() {
+        // No weight, but still takes up volume.
+        let cake_types = vec![CakeType::with_resources(&[0, 2], 3u32)];
+        let contents = multi_constraint_bag_contents(&cake_types, &[0, 7]).expect("finite");
+        assert_eq!(contents.counts, vec![3]);
+
+        let infinite = vec![CakeType::with_resources(&[0, 0], 3u32)];
+        assert_eq!(multi_constraint_bag_contents(&infinite, &[4, 4]), None);
+    }
+
+    #[test]
+    fn greedy_gap_when_greedy_approach_fails() {
+        let cake_types = vec![
+            CakeType::new(3, Rational::from(6)),
+            CakeType::new(2, Rational::new(39, 10)),
+            CakeType::new(1, Rational::from(1)),
+        ];
+
+        let report = greedy_gap_report(&cake_types, 4).expect("finite");
+        // Greedy takes the (3, 6) cake and fills the rest with (1, 1).
+        assert_eq!(report.greedy.counts, vec![1, 0, 1]);
+        assert_eq!(report.greedy.total_value, Rational::from(7));
+        assert_eq!(report.optimal.counts, vec![0, 2, 0]);
+        assert_eq!(report.optimal.total_value, Rational::new(78, 10));
+        // 4/3 of the (3, 6) cake.
+        assert_eq!(report.fractional.weights_taken, vec![4, 0, 0]);
+        assert_eq!(report.fractional.total_value, Rational::from(8));
+    }
+
+    #[test]
+    fn greedy_gap_with_quantities() {
+        let cake_types = vec![
+            CakeType::with_quantity(3, 90u32, 2),
+            CakeType::with_quantity(7, 160, 1),
+            CakeType::new(2, 15),
+            CakeType::with_quantity(0, 5, 2),
+        ];
+
+        let report = greedy_gap_report(&cake_types, 20).expect("finite");
+        assert_eq!(report.greedy.counts, vec![2, 1, 3, 2]);
+        assert_eq!(report.greedy.total_value, 395);
+        assert_eq!(report.optimal.total_value, 395);
+        // All of the first two cakes, then 7 of the (2, 15) cake's weight rounds up to 53.
+        assert_eq!(report.fractional.weights_taken, vec![6, 7, 7, 0]);
+        assert_eq!(report.fractional.total_value, 180 + 160 + 53 + 10);
+        assert!(report.greedy.total_value <= report.optimal.total_value);
+    }
+
+    #[test]
+    fn greedy_gap_infinite() {
+        let cake_types = vec![CakeType::new(0, 6.0), CakeType::new(1, 1.0)];
+        assert_eq!(greedy_gap_report(&cake_types, 4), None);
     }
 }
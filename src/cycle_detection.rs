@@ -0,0 +1,209 @@
+/*
+Problem:
+Repeatedly applying a function to a state, x_0 = start and x_(i + 1) = f(x_i), must eventually
+repeat if there are finitely many states. The sequence then looks like a rho: a tail of μ states
+that are never seen again, followed by a cycle of λ states that repeats forever. Find μ and λ
+without storing every state seen.
+
+All three algorithms first find some state inside the cycle. From there:
+- λ is the number of steps to get back to that state.
+- μ is found with two pointers λ steps apart, one starting at x_0. They first meet where the
+  cycle starts, after μ steps.
+
+Space complexity: O(1) states (O(log(μ + λ)) for Gosper).
+*/
+/// Shape of the sequence start, f(start), f(f(start)), ...
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// μ, the index of the first state that's part of the cycle.
+    pub tail_length: usize,
+    /// λ, the number of states in the cycle.
+    pub cycle_length: usize,
+}
+
+/*
+Floyd (tortoise and hare):
+The hare moves two steps for every step of the tortoise. Once both are in the cycle the hare gains
+one step every iteration, so they meet within λ iterations of the tortoise entering the cycle.
+
+Time complexity: O(μ + λ) applications of f, about 3 per iteration.
+*/
+pub fn floyd<S: Clone + PartialEq>(start: S, f: impl Fn(S) -> S) -> Cycle {
+    let mut tortoise = f(start.clone());
+    let mut hare = f(f(start.clone()));
+    while tortoise != hare {
+        tortoise = f(tortoise);
+        hare = f(f(hare));
+    }
+
+    let cycle_length = cycle_length(tortoise, &f);
+    Cycle {
+        tail_length: tail_length(start, &f, cycle_length),
+        cycle_length,
+    }
+}
+
+/*
+Brent:
+The tortoise teleports to the hare every time the hare has taken a power of two steps since the
+last teleport. Once the power of two is at least λ and the tortoise is in the cycle, the hare meets
+it, and the number of steps since the teleport is λ. Only one application of f per step.
+
+Time complexity: O(μ + λ) applications of f.
+*/
+pub fn brent<S: Clone + PartialEq>(start: S, f: impl Fn(S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut cycle_length = 1;
+    let mut tortoise = start.clone();
+    let mut hare = f(start.clone());
+    while tortoise != hare {
+        if power == cycle_length {
+            tortoise = hare.clone();
+            power *= 2;
+            cycle_length = 0;
+        }
+        hare = f(hare);
+        cycle_length += 1;
+    }
+
+    Cycle {
+        tail_length: tail_length(start, &f, cycle_length),
+        cycle_length,
+    }
+}
+
+/*
+Gosper:
+Keeps a small table of past states, where x_i is stored in slot k for k = the number of trailing
+zeros of i. Slot k is overwritten every 2^(k + 1) steps, so the table holds states from
+exponentially spaced points in the past, and every new state is compared to all of them. A state in
+the cycle stays in its slot long enough to be seen again once 2^k > λ, so a repeat is found within
+O(μ + λ) steps of a single application of f each.
+
+Time complexity: O((μ + λ) log(μ + λ)) comparisons, O(μ + λ) applications of f.
+*/
+pub fn gosper<S: Clone + PartialEq>(start: S, f: impl Fn(S) -> S) -> Cycle {
+    let mut table = vec![start.clone()];
+    let mut state = start.clone();
+    let mut step: u64 = 0;
+    let repeated = loop {
+        state = f(state);
+        step += 1;
+        if table.contains(&state) {
+            break state;
+        }
+
+        let slot = step.trailing_zeros() as usize;
+        if slot == table.len() {
+            table.push(state.clone());
+        } else {
+            table[slot] = state.clone();
+        }
+    };
+
+    // The repeat is some multiple of λ steps apart, so measure λ directly.
+    let cycle_length = cycle_length(repeated, &f);
+    Cycle {
+        tail_length: tail_length(start, &f, cycle_length),
+        cycle_length,
+    }
+}
+
+/// Steps to get back to a state that's in the cycle.
+fn cycle_length<S: Clone + PartialEq>(in_cycle: S, f: &impl Fn(S) -> S) -> usize {
+    let mut state = f(in_cycle.clone());
+    let mut length = 1;
+    while state != in_cycle {
+        state = f(state);
+        length += 1;
+    }
+    length
+}
+
+/// Walks two pointers cycle_length steps apart from start until they meet at the cycle start.
+fn tail_length<S: Clone + PartialEq>(start: S, f: &impl Fn(S) -> S, cycle_length: usize) -> usize {
+    let mut behind = start.clone();
+    let mut ahead = start;
+    for _ in 0..cycle_length {
+        ahead = f(ahead);
+    }
+
+    let mut length = 0;
+    while behind != ahead {
+        behind = f(behind);
+        ahead = f(ahead);
+        length += 1;
+    }
+    length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Remembers every state seen.
+    fn brute_force(start: u64, f: impl Fn(u64) -> u64) -> Cycle {
+        let mut first_seen = HashMap::new();
+        let mut state = start;
+        let mut step = 0;
+        loop {
+            if let Some(&seen) = first_seen.get(&state) {
+                return Cycle {
+                    tail_length: seen,
+                    cycle_length: step - seen,
+                };
+            }
+            first_seen.insert(state, step);
+            state = f(state);
+            step += 1;
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        // Pollard's rho style maps have a mix of tail and cycle lengths.
+        for modulus in 1..200 {
+            for &start in &[0, 1, 5, 17] {
+                let f = |x: u64| (x * x + 1) % modulus;
+                let expected = brute_force(start, f);
+                assert_eq!(floyd(start, f), expected, "modulus = {}", modulus);
+                assert_eq!(brent(start, f), expected, "modulus = {}", modulus);
+                assert_eq!(gosper(start, f), expected, "modulus = {}", modulus);
+            }
+        }
+    }
+
+    #[test]
+    fn pure_cycle_and_fixed_point() {
+        let rotate = |x: u64| (x + 1) % 10;
+        let expected = Cycle {
+            tail_length: 0,
+            cycle_length: 10,
+        };
+        assert_eq!(floyd(3, rotate), expected);
+        assert_eq!(brent(3, rotate), expected);
+        assert_eq!(gosper(3, rotate), expected);
+
+        let halve = |x: u64| x / 2;
+        let expected = Cycle {
+            tail_length: 7,
+            cycle_length: 1,
+        };
+        assert_eq!(floyd(100, halve), expected);
+        assert_eq!(brent(100, halve), expected);
+        assert_eq!(gosper(100, halve), expected);
+    }
+
+    #[test]
+    fn prng_period() {
+        // Full period linear congruential generator modulo 2^16.
+        let lcg = |x: u32| (x.wrapping_mul(25_173).wrapping_add(13_849)) & 0xffff;
+        let expected = Cycle {
+            tail_length: 0,
+            cycle_length: 1 << 16,
+        };
+        assert_eq!(brent(42, lcg), expected);
+        assert_eq!(gosper(42, lcg), expected);
+    }
+}
@@ -1,29 +1,266 @@
-// Computes the n-th fibonocci number.
+use crate::big_uint::BigUint;
+use std::convert::TryFrom;
+
+// Computes the n-th fibonocci number. Panics past n = 47, which doesn't fit in a u32.
 pub fn fib(n: u32) -> u32 {
-    if n == 0 {
-        return 0;
+    checked_fib(n as u64).unwrap_or_else(|| panic!("fib({}) overflows u32", n))
+}
+
+/// Computes the n-th fibonacci number, or None if it doesn't fit in T (past n = 47 for u32, 93
+/// for u64 and 186 for u128).
+pub fn checked_fib<T: FibonacciInteger>(n: u64) -> Option<T> {
+    // Overflow happens within 200 terms for every implementor, so stepping is cheap.
+    let n = usize::try_from(n).ok()?;
+    Fibonacci::new().nth(n)
+}
+
+/// Unsigned integers that Fibonacci can yield.
+pub trait FibonacciInteger: Copy {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_fibonacci_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl FibonacciInteger for $integer {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$integer>::checked_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_fibonacci_integer!(u8, u16, u32, u64, u128, usize);
+
+/// Yields 0, 1, 1, 2, 3, 5, ... and stops after the last number that fits in T.
+pub struct Fibonacci<T> {
+    current: Option<T>,
+    next: Option<T>,
+}
+
+impl<T: FibonacciInteger> Fibonacci<T> {
+    pub fn new() -> Self {
+        Self {
+            current: Some(T::zero()),
+            next: Some(T::one()),
+        }
     }
+}
 
-    if n == 1 {
-        return 1;
+impl<T: FibonacciInteger> Default for Fibonacci<T> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    let mut prev = 0;
-    let mut current = 1;
+impl<T: FibonacciInteger> Iterator for Fibonacci<T> {
+    type Item = T;
 
-    let mut n = (n as i64) - 2;
-    while n >= 0 {
-        let new_current = prev + current;
-        prev = current;
-        current = new_current;
-        n -= 1;
+    fn next(&mut self) -> Option<T> {
+        let value = self.current?;
+        self.current = self.next;
+        // Once an addition overflows, next stays None and the iterator ends one step later.
+        self.next = self.next.and_then(|next| value.checked_add(next));
+        Some(value)
     }
+}
+
+/*
+Zeckendorf decomposition:
+Every positive integer is a unique sum of non-consecutive fibonacci numbers (not using F(0) or
+both of the 1s). Greedily taking the largest fibonacci number that fits gives that sum: if F(k) is
+the largest that fits, the remainder is below F(k + 1) - F(k) = F(k - 1), so F(k - 1) can't be
+taken next.
+
+Time complexity: O(log value), there are O(log value) fibonacci numbers below value.
+Space complexity: O(log value)
+*/
+/// Splits value into non-consecutive fibonacci numbers, largest first. Empty for 0.
+pub fn zeckendorf(value: u64) -> Vec<u64> {
+    // 1, 2, 3, 5, ...
+    let candidates: Vec<u64> = Fibonacci::new().skip(2).collect();
+
+    let mut parts = vec![];
+    let mut remaining = value;
+    for &candidate in candidates.iter().rev() {
+        if candidate <= remaining {
+            parts.push(candidate);
+            remaining -= candidate;
+        }
+    }
+    parts
+}
+
+/*
+Fast doubling:
+Adding up the previous two numbers in a loop takes O(n) additions. Instead, using the identities
+F(2k) = F(k) * (2 * F(k + 1) - F(k))
+F(2k + 1) = F(k)^2 + F(k + 1)^2
+we can go from (F(k), F(k + 1)) to (F(2k), F(2k + 1)) with 3 multiplications, and from there to
+(F(2k + 1), F(2k + 2)) with an addition. Walking the bits of n from the most significant bit down
+builds up k one bit at a time until k = n.
+
+F(n) has ~0.694 * n bits, so fixed size integers overflow quickly and BigUint is used instead.
+
+Time complexity: O(log n) multiplications, each O(M^1.585) for M-bit numbers (Karatsuba).
+Space complexity: O(n) bits for the result.
+*/
+/// Computes the n-th fibonacci number with arbitrary precision.
+pub fn fib_big(n: u64) -> BigUint {
+    // (F(k), F(k + 1)), starting at k = 0.
+    let mut current = BigUint::zero();
+    let mut next = BigUint::one();
+
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        let doubled_next = &next + &next;
+        let even = &current * &(&doubled_next - &current);
+        let odd = &(&current * &current) + &(&next * &next);
+
+        if n >> bit & 1 == 1 {
+            next = &even + &odd;
+            current = odd;
+        } else {
+            current = even;
+            next = odd;
+        }
+    }
+
     current
 }
 
+/*
+Fibonacci modulo m:
+The same fast doubling works with every operation done modulo m, so the numbers never grow past
+m and n can be as big as u64::MAX. Products of two numbers below m are done in u128 so they can't
+overflow, but each is reduced before adding since the sum of two could.
+
+Time complexity: O(log n)
+Space complexity: O(1)
+*/
+/// Computes fib(n) % m. Panics if m is 0.
+pub fn fib_mod(n: u64, m: u64) -> u64 {
+    assert!(m != 0, "modulus must not be zero");
+    let m = m as u128;
+
+    // (F(k), F(k + 1)) % m, starting at k = 0.
+    let mut current = 0 % m;
+    let mut next = 1 % m;
+
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        let even = current * ((2 * next + m - current) % m) % m;
+        let odd = (current * current % m + next * next % m) % m;
+
+        if n >> bit & 1 == 1 {
+            next = (even + odd) % m;
+            current = odd;
+        } else {
+            current = even;
+            next = odd;
+        }
+    }
+
+    current as u64
+}
+
+/*
+Pisano periods:
+fib(n) % m repeats with period π(m), since the pair (F(k), F(k + 1)) % m determines the rest of the
+sequence and there are only m^2 possible pairs. Finding it by stepping through the sequence takes
+up to 6m steps, instead it's built from known divisibility results:
+- π(m) is the lcm of π(p^k) over the prime powers p^k in the factorization of m.
+- π(p^k) divides p^(k - 1) * π(p).
+- π(2) = 3 and π(5) = 20. For other primes π(p) divides p - 1 if p = ±1 (mod 10), and 2(p + 1)
+  otherwise.
+So for every prime power there's a known multiple of the period. The period is the smallest
+divisor d of that multiple with (F(d), F(d + 1)) = (0, 1), which is found by dividing out prime
+factors for as long as that still holds.
+
+Time complexity: O(sqrt(m)) for the trial division factoring, the rest is polylogarithmic.
+Space complexity: O(log m)
+*/
+/// Computes the Pisano period π(m), the period of fib(n) % m. Panics if m is 0 or the period
+/// doesn't fit in a u64 (only possible for m above u64::MAX / 6).
+pub fn pisano_period(m: u64) -> u64 {
+    assert!(m != 0, "modulus must not be zero");
+
+    let mut period: u64 = 1;
+    for (prime, exponent) in prime_factors(m) {
+        let prime_power = prime.pow(exponent);
+        let prime_period_multiple = match prime {
+            2 => 3,
+            5 => 20,
+            _ if prime % 10 == 1 || prime % 10 == 9 => prime - 1,
+            _ => 2 * (prime + 1),
+        };
+        let multiple = prime.pow(exponent - 1) as u128 * prime_period_multiple as u128;
+        let multiple = u64::try_from(multiple).expect("pisano period overflowed u64");
+
+        let mut prime_power_period = multiple;
+        for (factor, _) in prime_factors(multiple) {
+            while prime_power_period.is_multiple_of(factor)
+                && is_fib_period(prime_power_period / factor, prime_power)
+            {
+                prime_power_period /= factor;
+            }
+        }
+
+        period = lcm(period, prime_power_period);
+    }
+    period
+}
+
+fn is_fib_period(period: u64, m: u64) -> bool {
+    fib_mod(period, m) == 0 && fib_mod(period + 1, m) == 1 % m
+}
+
+/// (prime, exponent) pairs by trial division.
+fn prime_factors(mut value: u64) -> Vec<(u64, u32)> {
+    let mut factors = vec![];
+    let mut divisor = 2;
+    while divisor <= value / divisor {
+        let mut exponent = 0;
+        while value.is_multiple_of(divisor) {
+            value /= divisor;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((divisor, exponent));
+        }
+        divisor += 1;
+    }
+    if value > 1 {
+        factors.push((value, 1));
+    }
+    factors
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    let mut x = a;
+    let mut y = b;
+    while y != 0 {
+        let remainder = x % y;
+        x = y;
+        y = remainder;
+    }
+    let lcm = a as u128 / x as u128 * b as u128;
+    u64::try_from(lcm).expect("pisano period overflowed u64")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::linear_recurrence::LinearRecurrence;
 
     #[test]
     fn works() {
@@ -37,4 +274,153 @@ mod tests {
 
         assert_eq!(fib(47), 2_971_215_073);
     }
+
+    #[test]
+    #[should_panic(expected = "fib(48) overflows u32")]
+    fn panics_on_overflow() {
+        fib(48);
+    }
+
+    #[test]
+    fn iterator_stops_before_overflow() {
+        let first: Vec<u32> = Fibonacci::new().take(8).collect();
+        assert_eq!(first, vec![0, 1, 1, 2, 3, 5, 8, 13]);
+
+        assert_eq!(Fibonacci::<u32>::new().count(), 48);
+        assert_eq!(Fibonacci::<u64>::new().count(), 94);
+        assert_eq!(Fibonacci::<u128>::new().count(), 187);
+        assert_eq!(
+            Fibonacci::<u64>::new().last(),
+            Some(12_200_160_415_121_876_738)
+        );
+    }
+
+    #[test]
+    fn checked_matches_big() {
+        for n in 0..200 {
+            let big = fib_big(n);
+            assert_eq!(checked_fib::<u64>(n), big.to_u64());
+            assert_eq!(
+                checked_fib::<u32>(n),
+                big.to_u64().and_then(|value| u32::try_from(value).ok())
+            );
+        }
+        assert_eq!(
+            checked_fib::<u128>(186),
+            Some(332_825_110_087_067_562_321_196_029_789_634_457_848)
+        );
+        assert_eq!(checked_fib::<u128>(187), None);
+        assert_eq!(checked_fib::<u8>(u64::MAX), None);
+    }
+
+    #[test]
+    fn zeckendorf_is_non_consecutive() {
+        let fibs: Vec<u64> = Fibonacci::new().skip(2).collect();
+        let mut values: Vec<u64> = (0..2_000).collect();
+        values.push(u64::MAX);
+
+        for value in values {
+            let parts = zeckendorf(value);
+            assert_eq!(parts.iter().sum::<u64>(), value);
+
+            let indices: Vec<usize> = parts
+                .iter()
+                .map(|part| fibs.iter().position(|fib| fib == part).expect("fibonacci"))
+                .collect();
+            for pair in indices.windows(2) {
+                assert!(pair[0] >= pair[1] + 2, "{} = {:?}", value, parts);
+            }
+        }
+        assert_eq!(zeckendorf(0), vec![]);
+        assert_eq!(zeckendorf(100), vec![89, 8, 3]);
+    }
+
+    #[test]
+    fn big_matches_known_values() {
+        assert_eq!(fib_big(0), BigUint::zero());
+        assert_eq!(fib_big(1), BigUint::one());
+        let (mut prev, mut current) = (0u64, 1u64);
+        for n in 1..93 {
+            assert_eq!(fib_big(n).to_u64(), Some(current));
+            let next = prev + current;
+            prev = current;
+            current = next;
+        }
+        assert_eq!(fib_big(93).to_u64(), Some(12_200_160_415_121_876_738));
+        assert_eq!(
+            fib_big(300).to_string(),
+            "222232244629420445529739893461909967206666939096499764990979600"
+        );
+    }
+
+    #[test]
+    fn big_handles_millions() {
+        let value = fib_big(1_000_000);
+        assert_eq!(value.bits(), 694_241);
+        assert_eq!(value.rem_small(1_000_000_000), 242_546_875);
+    }
+
+    #[test]
+    fn mod_matches_fib() {
+        for n in 0..=47 {
+            for &m in &[1, 2, 3, 10, 1_000, 65_537, u32::MAX as u64, u64::MAX] {
+                assert_eq!(fib_mod(n, m), fib(n as u32) as u64 % m);
+            }
+        }
+        assert_eq!(fib_mod(1_000_000, 1_000_000_000), 242_546_875);
+    }
+
+    #[test]
+    fn mod_near_u64_max() {
+        for &m in &[u64::MAX, u64::MAX - 1, 18_446_744_073_709_551_557] {
+            let expected = LinearRecurrence::fibonacci().nth_mod(1_000_000, m);
+            assert_eq!(fib_mod(1_000_000, m), expected);
+            assert_eq!(
+                fib_mod(u64::MAX, m),
+                LinearRecurrence::fibonacci().nth_mod(u64::MAX, m)
+            );
+        }
+
+        // rem_small only takes u32 moduli.
+        let big = fib_big(100_000);
+        assert_eq!(
+            fib_mod(100_000, u32::MAX as u64),
+            big.rem_small(u32::MAX) as u64
+        );
+    }
+
+    #[test]
+    fn pisano_matches_stepping_through_sequence() {
+        for m in 1..300 {
+            // Step until the sequence gets back to (0, 1).
+            let (mut prev, mut current, mut period) = (0, 1 % m, 0);
+            loop {
+                let next = (prev + current) % m;
+                prev = current;
+                current = next;
+                period += 1;
+                if prev == 0 && current == 1 % m {
+                    break;
+                }
+            }
+            assert_eq!(pisano_period(m), period, "m = {}", m);
+        }
+    }
+
+    #[test]
+    fn pisano_known_values() {
+        assert_eq!(pisano_period(10), 60);
+        assert_eq!(pisano_period(1_000), 1_500);
+        assert_eq!(pisano_period(1_000_000_007), 2_000_000_016);
+    }
+
+    #[test]
+    fn mod_for_huge_n_repeats_with_period() {
+        for &m in &[7, 1_000, 1_000_000_007] {
+            let period = pisano_period(m);
+            for &n in &[u64::MAX, u64::MAX - 1, 1 << 62, 123_456_789_012_345] {
+                assert_eq!(fib_mod(n, m), fib_mod(n % period, m));
+            }
+        }
+    }
 }
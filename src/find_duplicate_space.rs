@@ -1,29 +1,92 @@
+use crate::cycle_detection::brent;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
 // Find the duplicate given the following constraints:
 // 1. The integers are in the range 1..n
 // 2. The list has a length of n+1
-pub fn find_duplicate(values: &Vec<i32>) -> i32 {
+pub fn find_duplicate(values: &[i32]) -> Result<i32, FindDuplicateError> {
+    validate(values, max_value(values.len()), 2)?;
+
     let binary_search_value = find_duplicate_binary_search(values);
     let graph_value = find_duplicate_graph(values);
-    assert_eq!(binary_search_value, graph_value);
-    binary_search_value
+    // With several duplicated values the two strategies can find different ones, so only check
+    // that both found a real duplicate.
+    for &duplicate in &[binary_search_value, graph_value] {
+        debug_assert!(values.iter().filter(|&&value| value == duplicate).count() > 1);
+    }
+    Ok(graph_value)
+}
+
+/// Why the values don't fit the constraints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindDuplicateError {
+    /// There are len values, but at least min_len are needed.
+    TooFewValues { len: usize, min_len: usize },
+    /// The first value that's outside of 1..=n.
+    OutOfRange { index: usize, value: i32, n: i32 },
+}
+
+impl fmt::Display for FindDuplicateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FindDuplicateError::TooFewValues { len, min_len } => {
+                write!(f, "got {} values, need at least {}", len, min_len)
+            }
+            FindDuplicateError::OutOfRange { index, value, n } => {
+                write!(
+                    f,
+                    "value {} at index {} is outside of 1..={}",
+                    value, index, n
+                )
+            }
+        }
+    }
+}
+
+impl Error for FindDuplicateError {}
+
+/// n for n + 1 values, values can't go past i32::MAX anyway.
+fn max_value(len: usize) -> i32 {
+    i32::try_from(len.saturating_sub(1)).unwrap_or(i32::MAX)
 }
 
-fn find_duplicate_binary_search(values: &Vec<i32>) -> i32 {
+fn validate(values: &[i32], n: i32, min_len: usize) -> Result<(), FindDuplicateError> {
+    if values.len() < min_len {
+        return Err(FindDuplicateError::TooFewValues {
+            len: values.len(),
+            min_len,
+        });
+    }
+    match values.iter().position(|&value| value < 1 || value > n) {
+        Some(index) => Err(FindDuplicateError::OutOfRange {
+            index,
+            value: values[index],
+            n,
+        }),
+        None => Ok(()),
+    }
+}
+
+// Expects validated values, otherwise there may be no duplicate and this never ends.
+fn find_duplicate_binary_search(values: &[i32]) -> i32 {
     let n = values.len() - 1;
 
     let mut lower = 1;
     let mut upper = n as i32;
 
+    // lower..=upper always holds more values than it has room for, so it has a duplicate.
     loop {
         let mid = (lower + upper) / 2;
         let mut in_lower = 0;
-        let mut in_upper = 0;
         let mut in_mid = 0;
         for &value in values {
             if lower <= value && value < mid {
                 in_lower += 1;
-            } else if mid < value && value <= upper {
-                in_upper += 1;
             } else if mid == value {
                 in_mid += 1;
             }
@@ -33,10 +96,12 @@ fn find_duplicate_binary_search(values: &Vec<i32>) -> i32 {
             return mid;
         }
 
-        if in_upper > in_lower {
-            lower = mid + 1;
+        // Comparing against the room on each side rather than against the other side, which can
+        // go wrong when there are several duplicates.
+        if in_lower > mid - lower {
+            upper = mid - 1;
         } else {
-            upper = mid;
+            lower = mid + 1;
         }
     }
 }
@@ -53,89 +118,481 @@ are no values to that position).
 
 The duplicate value is also the position at the beginning of the cycle.
 
-The solution is as follows:
-1. We can start from the head of the graph (the last element) and advance N
-   steps to ensure that we're in the cycle that must exist.
-2. We can then count the size of the cycle by advancing until we reach the same position.
-3. We can then find the beginning of the cycle by keeping two pointers, one at the head and
-   one which is CYCLE_SIZE positions ahead of the head. By advancing these pointers together,
-   we can find the beginning of the cycle when they are at the same position.
-4. The position of the beginning of the cycle is a duplicate value. (Return this).
-
-Time: O(N) - Ending up in the cycle takes N ops, finding the cycle is bounded by N, and
-   finding the beginning of the cycle is bounded by N.
+Walking from the head is the sequence head, f(head), f(f(head)), ... for f(index) = value - 1,
+so Brent's algorithm from cycle_detection finds μ, the number of steps until the beginning of the
+cycle. Walking μ steps from the head gives the duplicate value. (Return this).
+
+Time: O(N) - Brent's algorithm applies f O(μ + λ) times, both bounded by N.
 Space: O(1) - We end up keeping a constant number of pointers / counters.
 */
-fn find_duplicate_graph(values: &Vec<i32>) -> i32 {
+fn find_duplicate_graph(values: &[i32]) -> i32 {
     let head_index = values.len() - 1;
 
     // Follows the value at the current index to arrive to a new index.
     // Casting is okay since all values must be in range 1..n.
-    let advance_index = |index| (values[index] - 1) as usize;
+    let advance_index = |index: usize| (values[index] - 1) as usize;
+
+    let cycle = brent(head_index, advance_index);
+    let mut beginning_of_cycle = head_index;
+    for _ in 0..cycle.tail_length {
+        beginning_of_cycle = advance_index(beginning_of_cycle);
+    }
+
+    // The duplicate value is the "position" (index + 1).
+    (beginning_of_cycle + 1) as i32
+}
+
+/// Every value of 1..=n that appears more than once (with its count) or never, both ascending.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DuplicateReport {
+    pub duplicates: Vec<(i32, usize)>,
+    pub missing: Vec<i32>,
+}
+
+/*
+In-place report:
+Relaxes the constraints to at least n values in 1..n, with any number of duplicates and missing
+values.
+
+1. Cycle sort: move each value k to index k - 1 by swapping until every index either holds its own
+   value, or a copy of a value that's already at home. Every swap puts one value home, so there are
+   at most N swaps.
+2. Every index that's not home to its value now holds an extra copy. Clear it to 0 and count it at
+   the value's home by turning the home value negative (-2 for two copies, -3 for three, ...).
+3. Read off the report: 0 at home means missing, negative means duplicated.
+4. Restore the values: put each k back at its home and the extra copies in the cleared slots.
 
-    // Walk through the graph N times to ensure we're in the cycle.
-    let index_in_the_cycle = {
-        let mut curr = head_index;
-        for _ in 0..values.len() {
-            curr = advance_index(curr);
+Time complexity: O(N)
+Space complexity: O(1) besides the report.
+*/
+/// Reports duplicated and missing values of 1..=n. Leaves the values permuted so that every
+/// present k is at index k - 1, with the extra copies in ascending order in the remaining slots.
+/// Needs at least n values, otherwise some values wouldn't have a home index.
+pub fn duplicate_report_in_place(
+    values: &mut [i32],
+    n: i32,
+) -> Result<DuplicateReport, FindDuplicateError> {
+    validate(values, n, usize::try_from(n).unwrap_or(0))?;
+    let n = n as usize;
+
+    for index in 0..values.len() {
+        loop {
+            let home = values[index] as usize - 1;
+            if home == index || values[home] == values[index] {
+                break;
+            }
+            values.swap(index, home);
         }
-        curr
-    };
+    }
 
-    // Find size of cycle by walking until reaching the same index again.
-    let size_of_cycle = {
-        let mut next = advance_index(index_in_the_cycle);
-        let mut count = 1;
-        while next != index_in_the_cycle {
-            next = advance_index(next);
-            count += 1;
+    for index in 0..values.len() {
+        let value = values[index];
+        if value <= 0 || value as usize - 1 == index {
+            continue;
         }
-        count
-    };
-    debug_assert!(size_of_cycle > 0);
+        // Extra copy, the home index holds the value or its (negative) count.
+        let home = value as usize - 1;
+        values[index] = 0;
+        values[home] = if values[home] > 0 {
+            -2
+        } else {
+            values[home] - 1
+        };
+    }
 
-    // Walk two pointers together, one is size_of_cycle ahead.
-    // When the two pointers are at the same position, we've found the
-    // beginning of the cycle.
-    let mut behind = head_index;
-    let mut ahead = {
-        let mut i = head_index;
-        for _ in 0..size_of_cycle {
-            i = advance_index(i);
+    let mut report = DuplicateReport::default();
+    for (home, &slot) in values[..n].iter().enumerate() {
+        let value = (home + 1) as i32;
+        match slot {
+            0 => report.missing.push(value),
+            count if count < 0 => report.duplicates.push((value, -count as usize)),
+            _ => (),
         }
-        i
-    };
+    }
 
-    while behind != ahead {
-        behind = advance_index(behind);
-        ahead = advance_index(ahead);
+    let mut hole = 0;
+    for home in 0..n {
+        if values[home] >= 0 {
+            continue;
+        }
+        let extra_copies = -values[home] - 1;
+        let value = (home + 1) as i32;
+        values[home] = value;
+        for _ in 0..extra_copies {
+            while values[hole] != 0 {
+                hole += 1;
+            }
+            values[hole] = value;
+        }
     }
 
-    // Now that we're at the beginning of the cycle, the
-    // duplicate value is the "position" (index + 1).
-    (behind + 1) as i32
+    Ok(report)
+}
+
+/*
+Read-only report:
+Without modifying the values or using extra space, the only tool left is counting how many values
+fall in a range with a pass over the values, like the binary search solution. Walking up from 1:
+- If no values fall in the range, every value in it is missing and the next range is twice as wide.
+- If the range is a single value, the count is exact.
+- Otherwise, retry with half the range.
+
+Empty stretches are skipped in O(log N) passes, but every present value costs a pass or two, so
+it's O(N^2) in the worst case. That's expected: finding duplicates in read-only memory with O(1)
+space can't be done much faster.
+
+Time complexity: O(N * (P + M log N)) for P present values and M missing values.
+Space complexity: O(1) besides the report.
+*/
+/// Reports duplicated and missing values of 1..=n without modifying the values.
+pub fn duplicate_report(values: &[i32], n: i32) -> Result<DuplicateReport, FindDuplicateError> {
+    validate(values, n, 0)?;
+    let n = n as i64;
+
+    let mut report = DuplicateReport::default();
+    let mut lower: i64 = 1;
+    let mut width: i64 = 1;
+    while lower <= n {
+        let upper = lower + (width - 1).min(n - lower);
+        let count = values
+            .iter()
+            .filter(|&&value| lower <= value as i64 && value as i64 <= upper)
+            .count();
+
+        if count == 0 {
+            report.missing.extend(lower as i32..=upper as i32);
+            lower = upper + 1;
+            width *= 2;
+        } else if lower == upper {
+            if count > 1 {
+                report.duplicates.push((lower as i32, count));
+            }
+            lower += 1;
+            width = 1;
+        } else {
+            width = (upper - lower + 1) / 2;
+        }
+    }
+
+    Ok(report)
+}
+
+/*
+Out-of-core solution:
+When the values don't fit in memory, the binary search solution still works since it only needs
+sequential passes over the values. Each pass counts the values in the lower half of the range
+[lower, upper]. If there are more than the half's width, by the pigeonhole principle it holds a
+duplicate, otherwise the upper half does. The source is re-opened for every pass, with one extra
+pass at the start to count and validate the values.
+
+Time complexity: O(N log N), in at most 1 + ceil(log2(N)) passes.
+Space complexity: O(1), besides the read buffer.
+*/
+/// A duplicate found in streamed values, and how many passes over the values it took.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamedDuplicate {
+    pub duplicate: u32,
+    pub passes: usize,
+}
+
+/// Finds a duplicate in n + 1 little-endian u32s in 1..=n, calling open to start every pass over
+/// the values. Invalid values are an InvalidData error.
+pub fn find_duplicate_streamed<R: Read>(
+    mut open: impl FnMut() -> io::Result<R>,
+) -> io::Result<StreamedDuplicate> {
+    let (mut smallest, mut largest) = (u32::MAX, 0);
+    let len = read_values(open()?, |value| {
+        smallest = smallest.min(value);
+        largest = largest.max(value);
+    })?;
+    let mut passes = 1;
+
+    if len < 2 {
+        return Err(invalid_data(format!("got {} values, need at least 2", len)));
+    }
+    let n = len - 1;
+    if smallest < 1 || largest as u64 > n {
+        return Err(invalid_data(format!(
+            "values range from {} to {}, outside of 1..={}",
+            smallest, largest, n
+        )));
+    }
+
+    let (mut lower, mut upper) = (1, n);
+    while lower < upper {
+        let mid = lower + (upper - lower) / 2;
+        let mut in_lower_half = 0;
+        read_values(open()?, |value| {
+            if lower <= value as u64 && value as u64 <= mid {
+                in_lower_half += 1;
+            }
+        })?;
+        passes += 1;
+
+        if in_lower_half > mid - lower + 1 {
+            upper = mid;
+        } else {
+            lower = mid + 1;
+        }
+    }
+
+    Ok(StreamedDuplicate {
+        // Fits since it's at most the largest value.
+        duplicate: lower as u32,
+        passes,
+    })
+}
+
+/// find_duplicate_streamed over a file, opening it once per pass.
+pub fn find_duplicate_in_file(path: impl AsRef<Path>) -> io::Result<StreamedDuplicate> {
+    find_duplicate_streamed(|| File::open(path.as_ref()))
+}
+
+/// Calls visit with every little-endian u32, returns how many there were.
+fn read_values(source: impl Read, mut visit: impl FnMut(u32)) -> io::Result<u64> {
+    let mut reader = BufReader::new(source);
+    let mut bytes = [0; 4];
+    let mut count = 0;
+    loop {
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match reader.read(&mut bytes[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        match filled {
+            0 => return Ok(count),
+            4 => {
+                visit(u32::from_le_bytes(bytes));
+                count += 1;
+            }
+            _ => return Err(invalid_data("ends in the middle of a value".to_string())),
+        }
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::split_mix::SplitMix64;
+    use std::fs;
+    use std::io::Cursor;
+
+    fn to_bytes(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
 
     #[test]
     fn works() {
-        assert_eq!(find_duplicate(&vec![1, 3, 3, 2]), 3);
-        assert_eq!(find_duplicate(&vec![1, 2, 3, 2]), 2);
-        assert_eq!(find_duplicate(&vec![2, 3, 1, 1]), 1);
+        assert_eq!(find_duplicate(&[1, 3, 3, 2]), Ok(3));
+        assert_eq!(find_duplicate(&[1, 2, 3, 2]), Ok(2));
+        assert_eq!(find_duplicate(&[2, 3, 1, 1]), Ok(1));
     }
 
     #[test]
     fn ends_in_cycle_of_size_one() {
-        assert_eq!(find_duplicate(&vec![1, 2, 3, 2]), 2);
+        assert_eq!(find_duplicate(&[1, 2, 3, 2]), Ok(2));
     }
 
     #[test]
     fn more_examples() {
-        assert_eq!(find_duplicate(&vec![3, 4, 2, 3, 1, 5]), 3);
-        assert_eq!(find_duplicate(&vec![3, 1, 2, 2]), 2);
-        assert_eq!(find_duplicate(&vec![4, 3, 1, 1, 4]), 4);
+        assert_eq!(find_duplicate(&[3, 4, 2, 3, 1, 5]), Ok(3));
+        assert_eq!(find_duplicate(&[3, 1, 2, 2]), Ok(2));
+        assert_eq!(find_duplicate(&[4, 3, 1, 1, 4]), Ok(4));
+    }
+
+    #[test]
+    fn several_duplicates() {
+        // 1, 2 and 3 are all duplicated, any of them will do.
+        let duplicate = find_duplicate(&[2, 2, 3, 1, 4, 3, 1]).unwrap();
+        assert!([1, 2, 3].contains(&duplicate));
+        // Used to send the binary search into the wrong half, where it never finished.
+        let values = [6, 1, 10, 6, 2, 6, 13, 3, 9, 11, 13, 11, 4, 12];
+        assert!([6, 11, 13].contains(&find_duplicate_binary_search(&values)));
+
+        let mut rng = SplitMix64::new(44);
+        for _ in 0..20_000 {
+            let len = 2 + rng.next_below(20) as usize;
+            let values: Vec<i32> = (0..len)
+                .map(|_| 1 + rng.next_below(len as u64 - 1) as i32)
+                .collect();
+            let duplicate = find_duplicate(&values).unwrap();
+            assert!(
+                values.iter().filter(|&&value| value == duplicate).count() > 1,
+                "values = {:?}",
+                values
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_eq!(
+            find_duplicate(&[]),
+            Err(FindDuplicateError::TooFewValues { len: 0, min_len: 2 })
+        );
+        assert_eq!(
+            find_duplicate(&[1]),
+            Err(FindDuplicateError::TooFewValues { len: 1, min_len: 2 })
+        );
+        assert_eq!(
+            find_duplicate(&[1, 2, 0, 3]),
+            Err(FindDuplicateError::OutOfRange {
+                index: 2,
+                value: 0,
+                n: 3
+            })
+        );
+        // No duplicate possible since 4 is out of range.
+        assert_eq!(
+            find_duplicate(&[1, 2, 3, 4]).unwrap_err().to_string(),
+            "value 4 at index 3 is outside of 1..=3"
+        );
+
+        assert_eq!(
+            duplicate_report_in_place(&mut [1, 1], 3),
+            Err(FindDuplicateError::TooFewValues { len: 2, min_len: 3 })
+        );
+        assert_eq!(
+            duplicate_report(&[1, -5], 3),
+            Err(FindDuplicateError::OutOfRange {
+                index: 1,
+                value: -5,
+                n: 3
+            })
+        );
+    }
+
+    #[test]
+    fn reports_every_duplicate_and_missing_value() {
+        let expected = DuplicateReport {
+            duplicates: vec![(2, 3), (5, 2)],
+            missing: vec![1, 4, 6],
+        };
+        let mut values = vec![5, 2, 3, 2, 5, 2];
+        assert_eq!(duplicate_report(&values, 6), Ok(expected.clone()));
+        assert_eq!(duplicate_report_in_place(&mut values, 6), Ok(expected));
+        assert_eq!(values, vec![2, 2, 3, 2, 5, 5]);
+
+        // More values than the range, or fewer.
+        assert_eq!(
+            duplicate_report(&[1, 1, 1], 1).unwrap().duplicates,
+            vec![(1, 3)]
+        );
+        assert_eq!(duplicate_report(&[3], 3).unwrap().missing, vec![1, 2]);
+    }
+
+    #[test]
+    fn report_modes_match_counting() {
+        let mut rng = SplitMix64::new(7);
+        for &(len, n) in &[(10, 9), (50, 50), (100, 30), (30, 100), (1, 1)] {
+            let values: Vec<i32> = (0..len)
+                .map(|_| rng.next_below(n as u64) as i32 + 1)
+                .collect();
+
+            let mut counts = vec![0; n as usize + 1];
+            for &value in &values {
+                counts[value as usize] += 1;
+            }
+            let expected = DuplicateReport {
+                duplicates: (1..=n)
+                    .filter(|&value| counts[value as usize] > 1)
+                    .map(|value| (value, counts[value as usize]))
+                    .collect(),
+                missing: (1..=n)
+                    .filter(|&value| counts[value as usize] == 0)
+                    .collect(),
+            };
+
+            assert_eq!(duplicate_report(&values, n), Ok(expected.clone()));
+            if len < n {
+                continue;
+            }
+            let mut permuted = values.clone();
+            assert_eq!(duplicate_report_in_place(&mut permuted, n), Ok(expected));
+            let (mut sorted, mut original) = (permuted.clone(), values.clone());
+            sorted.sort_unstable();
+            original.sort_unstable();
+            assert_eq!(sorted, original);
+        }
+    }
+
+    #[test]
+    fn streams_values() {
+        let examples: Vec<Vec<u32>> = vec![
+            vec![1, 3, 3, 2],
+            vec![2, 3, 1, 1],
+            vec![3, 4, 2, 3, 1, 5],
+            vec![4, 3, 1, 1, 4],
+            (1..=1_000).chain(Some(777)).collect(),
+        ];
+        for values in examples {
+            let bytes = to_bytes(&values);
+            let mut opened = 0;
+            let found = find_duplicate_streamed(|| {
+                opened += 1;
+                Ok(Cursor::new(&bytes))
+            })
+            .unwrap();
+
+            let as_i32: Vec<i32> = values.iter().map(|&value| value as i32).collect();
+            assert_eq!(Ok(found.duplicate as i32), find_duplicate(&as_i32));
+            assert_eq!(found.passes, opened);
+            let n = values.len() - 1;
+            assert!(found.passes <= 1 + (n as f64).log2().ceil() as usize);
+        }
+    }
+
+    #[test]
+    fn streams_from_file() {
+        let path =
+            std::env::temp_dir().join(format!("find_duplicate_space_{}.bin", std::process::id()));
+        let values: Vec<u32> = (1..=100_000).rev().chain(Some(12_345)).collect();
+        fs::write(&path, to_bytes(&values)).unwrap();
+
+        let found = find_duplicate_in_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            found.unwrap(),
+            StreamedDuplicate {
+                duplicate: 12_345,
+                passes: 17,
+            }
+        );
+    }
+
+    #[test]
+    fn streaming_rejects_invalid_data() {
+        let find = |bytes: Vec<u8>| {
+            find_duplicate_streamed(|| Ok(Cursor::new(&bytes)))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(find(to_bytes(&[1])), "got 1 values, need at least 2");
+        assert_eq!(
+            find(to_bytes(&[1, 2, 3])),
+            "values range from 1 to 3, outside of 1..=2"
+        );
+        let mut partial = to_bytes(&[1, 1]);
+        partial.pop();
+        assert_eq!(find(partial), "ends in the middle of a value");
+
+        assert_eq!(
+            find_duplicate_in_file("/nonexistent/values.bin")
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
     }
 }
@@ -1,21 +1,182 @@
-pub fn find_rotation_point(list: &[&str]) -> usize {
+use std::cmp::Ordering;
+use std::iter::Chain;
+use std::slice::Iter;
+
+/*
+Problem:
+Find the rotation point of a sorted list that's been rotated, the index of its smallest element.
+
+Solution:
+Binary search comparing the middle element against the last one. If the middle is greater, the
+list wraps around somewhere after it, otherwise the middle is already past the rotation point.
+
+An unrotated list (including one with a single element) has its rotation point at 0, and an empty
+list has none.
+
+With runs of equal elements the middle can equal the last element, which says nothing about which
+side the rotation point is on ([1, 1, 1, 0, 1] vs [1, 0, 1, 1, 1]). The last element is dropped
+instead, after checking it isn't the rotation point itself. When several positions hold the
+smallest value, the rotation point is the first of them in sorted order, so the list read from
+there is sorted.
+
+Time complexity: O(log N), degrading to O(N) when most of the elements are equal.
+Space complexity: O(1)
+*/
+pub fn find_rotation_point<T: Ord>(list: &[T]) -> Option<usize> {
+    find_rotation_point_by(list, T::cmp)
+}
+
+/// find_rotation_point for a list that's sorted by key.
+pub fn find_rotation_point_by_key<T, K: Ord>(list: &[T], key: impl Fn(&T) -> K) -> Option<usize> {
+    find_rotation_point_by(list, |a, b| key(a).cmp(&key(b)))
+}
+
+/// find_rotation_point for a list that's sorted by compare.
+pub fn find_rotation_point_by<T>(
+    list: &[T],
+    compare: impl Fn(&T, &T) -> Ordering,
+) -> Option<usize> {
+    if list.is_empty() {
+        return None;
+    }
+
     let mut lower_index = 0;
     let mut upper_index = list.len() - 1;
 
-    while lower_index < upper_index - 1 {
-        let upper = list[upper_index];
+    while lower_index < upper_index {
+        let upper = &list[upper_index];
+
+        let mid_index = lower_index + (upper_index - lower_index) / 2;
+        let mid = &list[mid_index];
+
+        match compare(mid, upper) {
+            Ordering::Greater => lower_index = mid_index + 1,
+            Ordering::Less => upper_index = mid_index,
+            Ordering::Equal => {
+                if compare(&list[upper_index - 1], upper) == Ordering::Greater {
+                    return Some(upper_index);
+                }
+                upper_index -= 1;
+            }
+        }
+    }
 
-        let mid_index = (lower_index + upper_index) / 2;
-        let mid = list[mid_index];
+    Some(lower_index)
+}
+
+/*
+Searching a rotated list:
+Once the rotation point is known, the rotated list is two sorted runs: list[rotation_point..]
+followed by list[..rotation_point]. The i-th smallest element is at
+(rotation_point + i) % len, so binary searching over those sorted positions works the same as on
+a sorted list without having to copy it.
+
+Time complexity: O(log N) for each search, see find_rotation_point for finding the rotation point.
+Space complexity: O(1)
+*/
+/// Sorted view of a rotated sorted slice. Positions are in sorted order unless they're called
+/// slice indices.
+#[derive(Debug, Clone, Copy)]
+pub struct Rotated<'a, T> {
+    list: &'a [T],
+    rotation_point: usize,
+}
 
-        if mid > upper {
-            lower_index = mid_index;
+impl<'a, T: Ord> Rotated<'a, T> {
+    pub fn new(list: &'a [T]) -> Self {
+        Self {
+            list,
+            rotation_point: find_rotation_point(list).unwrap_or(0),
+        }
+    }
+
+    pub fn rotation_point(&self) -> usize {
+        self.rotation_point
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Slice index of the element at a sorted position.
+    pub fn slice_index(&self, position: usize) -> usize {
+        (self.rotation_point + position) % self.list.len()
+    }
+
+    /// Element at a sorted position.
+    pub fn get(&self, position: usize) -> Option<&'a T> {
+        if position < self.list.len() {
+            Some(&self.list[self.slice_index(position)])
         } else {
-            upper_index = mid_index;
+            None
+        }
+    }
+
+    pub fn min(&self) -> Option<&'a T> {
+        self.get(0)
+    }
+
+    pub fn max(&self) -> Option<&'a T> {
+        self.get(self.list.len().wrapping_sub(1))
+    }
+
+    /// Like slice::binary_search, Ok with the slice index of a matching element, or Err with the
+    /// sorted position where the value would go.
+    pub fn binary_search(&self, value: &T) -> Result<usize, usize> {
+        let position = self.lower_bound(value);
+        match self.get(position) {
+            Some(found) if found == value => Ok(self.slice_index(position)),
+            _ => Err(position),
         }
     }
 
-    upper_index
+    pub fn contains(&self, value: &T) -> bool {
+        self.binary_search(value).is_ok()
+    }
+
+    /// Sorted position of the first element that's not less than value.
+    pub fn lower_bound(&self, value: &T) -> usize {
+        self.partition_point(|element| element < value)
+    }
+
+    /// Sorted position of the first element that's greater than value.
+    pub fn upper_bound(&self, value: &T) -> usize {
+        self.partition_point(|element| element <= value)
+    }
+
+    /// Elements in sorted order.
+    pub fn iter(&self) -> Chain<Iter<'a, T>, Iter<'a, T>> {
+        let (end, start) = self.list.split_at(self.rotation_point);
+        start.iter().chain(end.iter())
+    }
+
+    /// First sorted position where is_before is false, is_before must be true then false.
+    fn partition_point(&self, is_before: impl Fn(&T) -> bool) -> usize {
+        let mut lower = 0;
+        let mut upper = self.list.len();
+        while lower < upper {
+            let mid = lower + (upper - lower) / 2;
+            if is_before(&self.list[self.slice_index(mid)]) {
+                lower = mid + 1;
+            } else {
+                upper = mid;
+            }
+        }
+        lower
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for Rotated<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Chain<Iter<'a, T>, Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 #[cfg(test)]
@@ -25,14 +186,116 @@ mod tests {
     #[test]
     fn works() {
         let words = vec!["p", "r", "s", "u", "x", "a", "b", "c", "e", "k", "o"];
-        assert_eq!(find_rotation_point(&words), 5);
+        assert_eq!(find_rotation_point(&words), Some(5));
 
         let words = vec![
             "p", "r", "s", "u", "x", "x", "x", "x", "y", "a", "b", "c", "e", "k", "o",
         ];
-        assert_eq!(find_rotation_point(&words), 9);
+        assert_eq!(find_rotation_point(&words), Some(9));
 
         let words = vec!["p", "s", "b", "o"];
-        assert_eq!(find_rotation_point(&words), 2);
+        assert_eq!(find_rotation_point(&words), Some(2));
+    }
+
+    #[test]
+    fn unrotated_and_tiny_lists() {
+        let empty: [i32; 0] = [];
+        assert_eq!(find_rotation_point(&empty), None);
+        assert_eq!(find_rotation_point(&[5]), Some(0));
+        assert_eq!(find_rotation_point(&[5, 6]), Some(0));
+        assert_eq!(find_rotation_point(&[6, 5]), Some(1));
+        assert_eq!(find_rotation_point(&[1, 2, 3, 4]), Some(0));
+    }
+
+    #[test]
+    fn runs_of_equal_elements() {
+        assert_eq!(find_rotation_point(&[1, 1, 1, 0, 1]), Some(3));
+        assert_eq!(find_rotation_point(&[1, 0, 1, 1, 1]), Some(1));
+        assert_eq!(find_rotation_point(&[2, 2, 2, 2]), Some(0));
+
+        // Every rotation of sorted lists with lots of repeats reads back sorted.
+        let lists: [&[i32]; 3] = [
+            &[0, 0, 1, 1, 1, 1, 2, 2, 3, 3, 3],
+            &[4, 4, 4, 4, 4, 4, 4, 5],
+            &[1, 2, 2, 2, 2, 2, 2, 2],
+        ];
+        for &sorted in &lists {
+            for rotation in 0..sorted.len() {
+                let mut list = sorted.to_vec();
+                list.rotate_left(rotation);
+                let point = find_rotation_point(&list).unwrap();
+                list.rotate_left(point);
+                assert_eq!(list, sorted, "rotation = {}", rotation);
+            }
+        }
+    }
+
+    #[test]
+    fn by_key_and_comparator() {
+        let words = ["kiwi", "fig", "banana", "melon"];
+        // Sorted by descending length.
+        assert_eq!(
+            find_rotation_point_by(&words, |a, b| b.len().cmp(&a.len())),
+            Some(2)
+        );
+        assert_eq!(
+            find_rotation_point_by_key(&[(3, 'a'), (1, 'b'), (2, 'c')], |pair| pair.0),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn rotated_searches_like_sorted() {
+        let sorted: Vec<i32> = (0..20).map(|value| value * 2).collect();
+        for rotation in 0..sorted.len() {
+            let mut list = sorted.clone();
+            list.rotate_left(rotation);
+            let rotated = Rotated::new(&list);
+
+            assert_eq!(rotated.iter().copied().collect::<Vec<_>>(), sorted);
+            assert_eq!(rotated.min(), Some(&0));
+            assert_eq!(rotated.max(), Some(&38));
+
+            for value in -1..40 {
+                let position = sorted.partition_point(|&element| element < value);
+                assert_eq!(rotated.lower_bound(&value), position);
+                assert_eq!(
+                    rotated.upper_bound(&value),
+                    sorted.partition_point(|&element| element <= value)
+                );
+                match rotated.binary_search(&value) {
+                    Ok(index) => assert_eq!(list[index], value),
+                    Err(insert_at) => {
+                        assert_eq!(insert_at, position);
+                        assert!(!sorted.contains(&value));
+                    }
+                }
+                assert_eq!(rotated.contains(&value), value % 2 == 0 && value >= 0);
+            }
+        }
+    }
+
+    #[test]
+    fn rotated_small_lists() {
+        let empty: [i32; 0] = [];
+        let rotated = Rotated::new(&empty);
+        assert!(rotated.is_empty());
+        assert_eq!(rotated.min(), None);
+        assert_eq!(rotated.max(), None);
+        assert_eq!(rotated.binary_search(&1), Err(0));
+        assert_eq!(rotated.iter().count(), 0);
+
+        let rotated = Rotated::new(&[7]);
+        assert_eq!(rotated.binary_search(&7), Ok(0));
+        assert_eq!(rotated.upper_bound(&7), 1);
+
+        let words = ["k", "v", "a", "d"];
+        let rotated = Rotated::new(&words[..]);
+        assert_eq!(rotated.rotation_point(), 2);
+        assert_eq!(rotated.binary_search(&"d"), Ok(3));
+        assert_eq!(
+            rotated.into_iter().copied().collect::<Vec<_>>(),
+            vec!["a", "d", "k", "v"]
+        );
     }
 }
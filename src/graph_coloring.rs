@@ -12,6 +12,8 @@ Naive solution:
     2a. Find all unavailable colors from adj nodes.
     2b. Choose first available color.
     2c. Add newly colored nodes to be visited.
+3. Repeat from every node that's still not colored, since the graph
+   may have several connected components.
 
 Time complexity: O(N * D)
     - Visit each node and do O(D) operations.
@@ -22,24 +24,66 @@ Space complexity: O(N)
     - Breadth-first traversal queue is at most O(N) size.
 */
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct NodeId(u32);
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ColorId(usize);
 
+/// Connected components found while coloring, ordered by their smallest node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColoringSummary {
+    pub component_count: usize,
+    /// Sorted colors used by each component.
+    pub colors_per_component: Vec<Vec<ColorId>>,
+}
+
 pub fn color_undirected_graph_with_max_degree(
     adjacency_table: &HashMap<NodeId, Vec<NodeId>>,
     max_degree: usize,
 ) -> HashMap<NodeId, ColorId> {
-    if adjacency_table.is_empty() {
-        return HashMap::new();
-    }
+    color_undirected_graph_with_summary(adjacency_table, max_degree).0
+}
 
+pub fn color_undirected_graph_with_summary(
+    adjacency_table: &HashMap<NodeId, Vec<NodeId>>,
+    max_degree: usize,
+) -> (HashMap<NodeId, ColorId>, ColoringSummary) {
     let mut coloring = HashMap::new();
+    let mut summary = ColoringSummary::default();
+
+    // Start from nodes in order so components are found in a stable order.
+    let mut start_node_ids: Vec<NodeId> = adjacency_table.keys().copied().collect();
+    start_node_ids.sort_unstable();
+    for node_id in start_node_ids {
+        if coloring.contains_key(&node_id) {
+            continue;
+        }
+
+        let component = color_component(adjacency_table, max_degree, node_id, &mut coloring);
+        let mut colors: Vec<ColorId> = component
+            .iter()
+            .map(|id| coloring[id])
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        colors.sort_unstable();
+
+        summary.component_count += 1;
+        summary.colors_per_component.push(colors);
+    }
+
+    (coloring, summary)
+}
 
-    // Get a random element in the adjacency_table.
-    let (&node_id, _) = adjacency_table.iter().next().expect("exists");
+/// Colors every node reachable from node_id, returns the nodes colored.
+fn color_component(
+    adjacency_table: &HashMap<NodeId, Vec<NodeId>>,
+    max_degree: usize,
+    node_id: NodeId,
+    coloring: &mut HashMap<NodeId, ColorId>,
+) -> Vec<NodeId> {
     coloring.insert(node_id, ColorId(0));
+    let mut component = vec![node_id];
 
     // Breadth-first traversal through the graph.
     let mut node_ids = VecDeque::new();
@@ -69,13 +113,14 @@ pub fn color_undirected_graph_with_max_degree(
                 available_color_id.expect("always availabe color")
             };
             coloring.insert(adj_node_id, ColorId(available_color_id));
+            component.push(adj_node_id);
 
             // Need to visit this adj node as well.
             node_ids.push_back(adj_node_id);
         }
     }
 
-    coloring
+    component
 }
 
 #[cfg(test)]
@@ -147,4 +192,39 @@ mod tests {
         let coloring = color_undirected_graph_with_max_degree(&adjacency_table, 0);
         assert_coloring_valid_for_undirected_graph(&coloring, &adjacency_table);
     }
+
+    #[test]
+    fn colors_every_component() {
+        // Graph looks like:
+        //  (1)---(2)   (3)   (4)---(5)
+        //          \          \   /
+        //          (6)         (7)
+        let mut adjacency_table = HashMap::new();
+        adjacency_table.insert(NodeId(1), vec![NodeId(2)]);
+        adjacency_table.insert(NodeId(2), vec![NodeId(1), NodeId(6)]);
+        adjacency_table.insert(NodeId(3), vec![]);
+        adjacency_table.insert(NodeId(4), vec![NodeId(5), NodeId(7)]);
+        adjacency_table.insert(NodeId(5), vec![NodeId(4), NodeId(7)]);
+        adjacency_table.insert(NodeId(6), vec![NodeId(2)]);
+        adjacency_table.insert(NodeId(7), vec![NodeId(4), NodeId(5)]);
+
+        let (coloring, summary) = color_undirected_graph_with_summary(&adjacency_table, 2);
+        assert_eq!(coloring.len(), 7);
+        assert_coloring_valid_for_undirected_graph(&coloring, &adjacency_table);
+        assert_eq!(
+            summary,
+            ColoringSummary {
+                component_count: 3,
+                colors_per_component: vec![
+                    vec![ColorId(0), ColorId(1)],
+                    vec![ColorId(0)],
+                    vec![ColorId(0), ColorId(1), ColorId(2)],
+                ],
+            }
+        );
+
+        let (coloring, summary) = color_undirected_graph_with_summary(&HashMap::new(), 0);
+        assert!(coloring.is_empty());
+        assert_eq!(summary, ColoringSummary::default());
+    }
 }
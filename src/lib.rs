@@ -1,10 +1,13 @@
 pub mod airport_gates;
 pub mod apple_stocks;
+pub mod big_uint;
+pub mod binary_tree_rebalance;
 pub mod binary_tree_search;
 pub mod binary_tree_second_largest;
 pub mod binary_tree_superbalanced;
 pub mod cafe_orders;
 pub mod cake_thief;
+pub mod cycle_detection;
 pub mod fib;
 pub mod find_duplicate_space;
 pub mod find_rotation_point;
@@ -13,14 +16,20 @@ pub mod graph_shortest_path;
 pub mod highest_product_of_three;
 pub mod highlight_search;
 pub mod inflight_entertainment;
+pub mod linear_recurrence;
 pub mod making_change;
 pub mod merge_lists;
 pub mod merging_meetings;
 pub mod needle_in_haystack;
 pub mod permutation_palindrome;
 pub mod product_of_all_other_numbers;
+pub mod rational;
 pub mod rectangular_love;
 pub mod reverse_words;
+mod split_mix;
 pub mod string_permutations;
+pub mod string_rotation;
 pub mod subsequence_reconstruction;
+#[cfg(test)]
+mod test_trees;
 pub mod tree_traversals;
@@ -0,0 +1,562 @@
+use std::convert::TryFrom;
+
+/*
+Problem:
+Fibonacci is one case of a linear recurrence, a(n) = c_1 * a(n - 1) + ... + c_k * a(n - k) with
+the first k terms given. Support any such recurrence (Lucas numbers, tribonacci, Pell, ...).
+
+Solution:
+The last k terms (a(j + k - 1), ..., a(j)) as a vector can be moved one step forward by
+multiplying with the companion matrix:
+| c_1 c_2 ... c_k |
+|  1   0  ...  0  |
+|  0   1  ...  0  |
+|  0   0  ... 1 0 |
+So a(n) is the first entry of M^(n - k + 1) applied to the initial terms, and M^e can be computed
+with O(log e) matrix multiplications by repeated squaring.
+
+Time complexity: O(k^3 log n)
+Space complexity: O(k^2)
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearRecurrence {
+    /// c_1 to c_k, a(n) = c_1 * a(n - 1) + ... + c_k * a(n - k).
+    coefficients: Vec<i64>,
+    /// a(0) to a(k - 1).
+    initial: Vec<i64>,
+}
+
+impl LinearRecurrence {
+    pub fn new(coefficients: Vec<i64>, initial: Vec<i64>) -> Self {
+        assert!(
+            !coefficients.is_empty(),
+            "recurrence needs at least one term"
+        );
+        assert_eq!(
+            coefficients.len(),
+            initial.len(),
+            "need one initial term per coefficient"
+        );
+        Self {
+            coefficients,
+            initial,
+        }
+    }
+
+    /// 0, 1, 1, 2, 3, 5, ...
+    pub fn fibonacci() -> Self {
+        Self::new(vec![1, 1], vec![0, 1])
+    }
+
+    /// 2, 1, 3, 4, 7, 11, ...
+    pub fn lucas() -> Self {
+        Self::new(vec![1, 1], vec![2, 1])
+    }
+
+    /// 0, 0, 1, 1, 2, 4, 7, 13, ...
+    pub fn tribonacci() -> Self {
+        Self::new(vec![1, 1, 1], vec![0, 0, 1])
+    }
+
+    /// 0, 1, 2, 5, 12, 29, ...
+    pub fn pell() -> Self {
+        Self::new(vec![2, 1], vec![0, 1])
+    }
+
+    pub fn coefficients(&self) -> &[i64] {
+        &self.coefficients
+    }
+
+    pub fn initial(&self) -> &[i64] {
+        &self.initial
+    }
+
+    /// Panics if the term doesn't fit in an i64.
+    pub fn nth(&self, n: u64) -> i64 {
+        self.checked_nth(n)
+            .unwrap_or_else(|| panic!("term {} overflowed i64", n))
+    }
+
+    /// None if the term doesn't fit in an i64. Matrix entries are kept in i128, so intermediate
+    /// values can grow well past the result, but recurrences with negative coefficients whose
+    /// matrix powers grow much faster than their terms may still give None.
+    pub fn checked_nth(&self, n: u64) -> Option<i64> {
+        let k = self.coefficients.len() as u64;
+        if n < k {
+            return Some(self.initial[n as usize]);
+        }
+
+        let matrix = self.companion_matrix(|coefficient| coefficient as i128);
+        let power = matrix_power(&matrix, n - k + 1, 0, 1, |sum, a, b| {
+            sum.checked_add(a.checked_mul(b)?)
+        })?;
+
+        // First entry of power * (a(k - 1), ..., a(0)).
+        let mut term: i128 = 0;
+        for (entry, &initial) in power[0].iter().zip(self.initial.iter().rev()) {
+            term = term.checked_add(entry.checked_mul(initial as i128)?)?;
+        }
+        i64::try_from(term).ok()
+    }
+
+    /// Computes a(n) modulo m, negative terms wrap around to [0, m). Panics if m is 0.
+    pub fn nth_mod(&self, n: u64, m: u64) -> u64 {
+        assert!(m != 0, "modulus must not be zero");
+        let modulus = m as i128;
+        let reduce = |value: i64| (value as i128).rem_euclid(modulus) as u128;
+
+        let k = self.coefficients.len() as u64;
+        if n < k {
+            return reduce(self.initial[n as usize]) as u64;
+        }
+
+        let m = m as u128;
+        let matrix = self.companion_matrix(reduce);
+        let power = matrix_power(&matrix, n - k + 1, 0, 1 % m, |sum, a, b| {
+            Some((sum + a * b % m) % m)
+        })
+        .expect("modular arithmetic can't overflow");
+
+        let mut term = 0;
+        for (entry, &initial) in power[0].iter().zip(self.initial.iter().rev()) {
+            term = (term + entry * reduce(initial) % m) % m;
+        }
+        term as u64
+    }
+
+    /// Lazily yields a(0), a(1), ... and stops before the first term that doesn't fit in an i64.
+    pub fn terms(&self) -> Terms {
+        Terms {
+            coefficients: self.coefficients.clone(),
+            // Most recent term last.
+            window: self.initial.clone(),
+            next_initial: 0,
+            overflowed: false,
+        }
+    }
+
+    fn companion_matrix<T: Copy>(&self, convert: impl Fn(i64) -> T) -> Vec<Vec<T>> {
+        let k = self.coefficients.len();
+        let zero = convert(0);
+        let one = convert(1);
+
+        let mut matrix = vec![vec![zero; k]; k];
+        for (column, &coefficient) in self.coefficients.iter().enumerate() {
+            matrix[0][column] = convert(coefficient);
+        }
+        for row in 1..k {
+            matrix[row][row - 1] = one;
+        }
+        matrix
+    }
+}
+
+/// Repeated squaring. mul_add(sum, a, b) returns sum + a * b, or None on overflow.
+fn matrix_power<T: Copy>(
+    matrix: &[Vec<T>],
+    mut exponent: u64,
+    zero: T,
+    one: T,
+    mul_add: impl Fn(T, T, T) -> Option<T>,
+) -> Option<Vec<Vec<T>>> {
+    let multiply = |a: &[Vec<T>], b: &[Vec<T>]| -> Option<Vec<Vec<T>>> {
+        let k = a.len();
+        let mut product = vec![vec![zero; k]; k];
+        for row in 0..k {
+            for column in 0..k {
+                let mut sum = zero;
+                for inner in 0..k {
+                    sum = mul_add(sum, a[row][inner], b[inner][column])?;
+                }
+                product[row][column] = sum;
+            }
+        }
+        Some(product)
+    };
+
+    let k = matrix.len();
+    let mut result = vec![vec![zero; k]; k];
+    for (index, row) in result.iter_mut().enumerate() {
+        row[index] = one;
+    }
+
+    let mut base = matrix.to_vec();
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply(&result, &base)?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = multiply(&base, &base)?;
+        }
+    }
+    Some(result)
+}
+
+/// See LinearRecurrence::terms.
+pub struct Terms {
+    coefficients: Vec<i64>,
+    window: Vec<i64>,
+    next_initial: usize,
+    overflowed: bool,
+}
+
+impl Iterator for Terms {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        if self.next_initial < self.window.len() {
+            self.next_initial += 1;
+            return Some(self.window[self.next_initial - 1]);
+        }
+        if self.overflowed {
+            return None;
+        }
+
+        let mut term: i64 = 0;
+        for (&coefficient, &previous) in self.coefficients.iter().zip(self.window.iter().rev()) {
+            match coefficient
+                .checked_mul(previous)
+                .and_then(|product| term.checked_add(product))
+            {
+                Some(sum) => term = sum,
+                None => {
+                    self.overflowed = true;
+                    return None;
+                }
+            }
+        }
+
+        self.window.remove(0);
+        self.window.push(term);
+        Some(term)
+    }
+}
+
+/*
+Berlekamp-Massey:
+Finds the shortest linear recurrence that generates a sequence prefix. It keeps a recurrence C
+that generates the prefix seen so far. When C predicts the next term wrong by d, it's fixed by
+subtracting a multiple of the last recurrence that failed (B, which failed by b), shifted so its
+error lines up with the current term: C = C - (d / b) * x^m * B. If the fix needs more terms than
+C had, C becomes the new B.
+
+A recurrence of length L is only determined by at least 2L terms, so give it enough of the
+sequence.
+
+The coefficients can be fractions even when the terms are integers, and their numerators and
+denominators grow much larger than the terms, so the search is done modulo the prime
+p = 2^127 - 1 instead, where every fraction is a single number below p. An integer coefficient c
+comes out as c mod p, which is lifted back to the integer closest to zero. The lifted recurrence
+is then checked against every term with exact arithmetic, so a recurrence is only returned if it
+really generates the sequence, and it's the shortest one since no recurrence is shorter modulo p.
+A discrepancy that's nonzero could in principle be a multiple of p and look like 0, which would
+give a wrong recurrence that the check rejects, but the chance of that is about 1 in 2^127.
+
+Time complexity: O(N^2) for N terms.
+Space complexity: O(N)
+*/
+/// Recovers the shortest linear recurrence generating the sequence. Panics if the sequence is
+/// empty. None if the shortest recurrence needs coefficients that aren't integers or don't fit in
+/// an i64.
+pub fn berlekamp_massey(sequence: &[i64]) -> Option<LinearRecurrence> {
+    assert!(!sequence.is_empty(), "need at least one term");
+    let residues: Vec<u128> = sequence.iter().map(|&term| to_residue(term)).collect();
+
+    // Connection polynomials, current[0] is always 1 and a(n) = -(current[1] * a(n - 1) + ...).
+    let mut current = vec![1];
+    let mut previous = vec![1];
+    let mut length = 0;
+    let mut shift = 1;
+    let mut previous_discrepancy = 1;
+
+    for n in 0..residues.len() {
+        let mut discrepancy = residues[n];
+        for i in 1..=length {
+            discrepancy = add_mod(discrepancy, mul_mod(current[i], residues[n - i]));
+        }
+
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+
+        let scale = mul_mod(discrepancy, inverse_mod(previous_discrepancy));
+        let before_fix = current.clone();
+        if current.len() < previous.len() + shift {
+            current.resize(previous.len() + shift, 0);
+        }
+        for (i, &term) in previous.iter().enumerate() {
+            current[i + shift] = sub_mod(current[i + shift], mul_mod(scale, term));
+        }
+
+        if 2 * length <= n {
+            length = n + 1 - length;
+            previous = before_fix;
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+
+    if length == 0 {
+        // All zeros.
+        return Some(LinearRecurrence::new(vec![0], vec![0]));
+    }
+
+    current.resize(length + 1, 0);
+    let coefficients = current[1..=length]
+        .iter()
+        .map(|&term| from_residue(sub_mod(0, term)))
+        .collect::<Option<Vec<i64>>>()?;
+
+    // Exact check of a(n) - (c_1 * a(n - 1) + ... + c_k * a(n - k)) == 0. It's zero modulo p and
+    // modulo 2^128 (wrapping i128 arithmetic), so it's zero modulo their product, which is far
+    // bigger than the sum could be.
+    for n in length..sequence.len() {
+        let mut wrapped = sequence[n] as i128;
+        let mut residue = residues[n];
+        for (i, &coefficient) in coefficients.iter().enumerate() {
+            let term = sequence[n - i - 1];
+            wrapped = wrapped.wrapping_sub((coefficient as i128).wrapping_mul(term as i128));
+            residue = sub_mod(
+                residue,
+                mul_mod(to_residue(coefficient), residues[n - i - 1]),
+            );
+        }
+        if wrapped != 0 || residue != 0 {
+            return None;
+        }
+    }
+
+    Some(LinearRecurrence::new(
+        coefficients,
+        sequence[..length].to_vec(),
+    ))
+}
+
+/// 2^127 - 1, a Mersenne prime, so reducing modulo it is a shift and an add.
+const PRIME: u128 = (1 << 127) - 1;
+
+fn to_residue(value: i64) -> u128 {
+    if value < 0 {
+        PRIME - (value as i128).unsigned_abs()
+    } else {
+        value as u128
+    }
+}
+
+/// The integer closest to zero with this residue, if it fits in an i64.
+fn from_residue(residue: u128) -> Option<i64> {
+    if residue > PRIME / 2 {
+        i64::try_from(-((PRIME - residue) as i128)).ok()
+    } else {
+        i64::try_from(residue).ok()
+    }
+}
+
+/// Reduces any u128, using 2^127 = 1 (mod PRIME).
+fn reduce(value: u128) -> u128 {
+    let reduced = (value & PRIME) + (value >> 127);
+    if reduced >= PRIME {
+        reduced - PRIME
+    } else {
+        reduced
+    }
+}
+
+fn add_mod(a: u128, b: u128) -> u128 {
+    // Both are below 2^127, so the sum can't overflow.
+    reduce(a + b)
+}
+
+fn sub_mod(a: u128, b: u128) -> u128 {
+    add_mod(a, PRIME - b)
+}
+
+fn mul_mod(a: u128, b: u128) -> u128 {
+    // Split into 64 bit halves, a * b = high * 2^128 + middle * 2^64 + low, and 2^128 = 2.
+    const LOW_BITS: u128 = (1 << 64) - 1;
+    let (a_high, a_low) = (a >> 64, a & LOW_BITS);
+    let (b_high, b_low) = (b >> 64, b & LOW_BITS);
+    let high = a_high * b_high;
+    let middle = a_high * b_low + a_low * b_high;
+    let low = a_low * b_low;
+
+    // middle * 2^64 = (middle >> 64) * 2^128 + (middle & LOW_BITS) * 2^64.
+    let wrapped = reduce(2 * high) + reduce(2 * (middle >> 64));
+    add_mod(
+        reduce(wrapped),
+        add_mod(reduce((middle & LOW_BITS) << 64), reduce(low)),
+    )
+}
+
+/// By Fermat's little theorem, a^(p - 2) * a = 1.
+fn inverse_mod(a: u128) -> u128 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = PRIME - 2;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base);
+        }
+        base = mul_mod(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fib::{fib, fib_mod};
+
+    #[test]
+    fn fibonacci_is_an_instance() {
+        let fibonacci = LinearRecurrence::fibonacci();
+        for n in 0..=47 {
+            assert_eq!(fibonacci.nth(n), fib(n as u32) as i64);
+        }
+        assert_eq!(fibonacci.checked_nth(92), Some(7_540_113_804_746_346_429));
+        assert_eq!(fibonacci.checked_nth(93), None);
+        for &n in &[1_000, 1 << 40, u64::MAX] {
+            assert_eq!(
+                fibonacci.nth_mod(n, 1_000_000_007),
+                fib_mod(n, 1_000_000_007)
+            );
+        }
+    }
+
+    #[test]
+    fn known_sequences() {
+        let first_terms =
+            |recurrence: LinearRecurrence| -> Vec<i64> { recurrence.terms().take(10).collect() };
+        assert_eq!(
+            first_terms(LinearRecurrence::lucas()),
+            vec![2, 1, 3, 4, 7, 11, 18, 29, 47, 76]
+        );
+        assert_eq!(
+            first_terms(LinearRecurrence::tribonacci()),
+            vec![0, 0, 1, 1, 2, 4, 7, 13, 24, 44]
+        );
+        assert_eq!(
+            first_terms(LinearRecurrence::pell()),
+            vec![0, 1, 2, 5, 12, 29, 70, 169, 408, 985]
+        );
+    }
+
+    #[test]
+    fn matrix_power_matches_iterator() {
+        let recurrences = vec![
+            LinearRecurrence::lucas(),
+            LinearRecurrence::tribonacci(),
+            LinearRecurrence::pell(),
+            // Alternating signs.
+            LinearRecurrence::new(vec![-1, 3, -2], vec![5, -4, 7]),
+        ];
+
+        for recurrence in recurrences {
+            let terms: Vec<i64> = recurrence.terms().collect();
+            // The iterator stops cleanly on overflow.
+            assert!(terms.len() > 30 && terms.len() < 100);
+            for (n, &term) in terms.iter().enumerate() {
+                assert_eq!(recurrence.checked_nth(n as u64), Some(term));
+                assert_eq!(
+                    recurrence.nth_mod(n as u64, 1_000),
+                    term.rem_euclid(1_000) as u64
+                );
+            }
+            assert_eq!(recurrence.checked_nth(terms.len() as u64), None);
+        }
+    }
+
+    #[test]
+    fn recovers_recurrences() {
+        for recurrence in [
+            LinearRecurrence::fibonacci(),
+            LinearRecurrence::tribonacci(),
+            LinearRecurrence::pell(),
+            LinearRecurrence::new(vec![-1, 3, -2], vec![5, -4, 7]),
+        ] {
+            let prefix: Vec<i64> = recurrence.terms().take(20).collect();
+            assert_eq!(berlekamp_massey(&prefix), Some(recurrence));
+        }
+
+        // Powers of two are a(n) = 2 * a(n - 1).
+        let recovered = berlekamp_massey(&[1, 2, 4, 8, 16, 32]).expect("integer");
+        assert_eq!(recovered.coefficients(), &[2]);
+        assert_eq!(recovered.initial(), &[1]);
+
+        // Terms near the top of the i64 range, the fractions along the way get much bigger.
+        let fibonacci = LinearRecurrence::fibonacci();
+        for &start in &[40, 50, 60, 70, 80, 85] {
+            let late_fibonacci: Vec<i64> = (start..start + 8).map(|n| fibonacci.nth(n)).collect();
+            assert_eq!(
+                berlekamp_massey(&late_fibonacci),
+                Some(LinearRecurrence::new(
+                    vec![1, 1],
+                    late_fibonacci[..2].to_vec()
+                )),
+                "start = {}",
+                start
+            );
+        }
+        let tribonacci = LinearRecurrence::tribonacci();
+        let late_tribonacci: Vec<i64> = (60..70).map(|n| tribonacci.nth(n)).collect();
+        assert_eq!(
+            berlekamp_massey(&late_tribonacci),
+            Some(LinearRecurrence::new(
+                vec![1, 1, 1],
+                late_tribonacci[..3].to_vec()
+            ))
+        );
+        let powers: Vec<i64> = (30..40).map(|exponent| 1 << exponent).collect();
+        assert_eq!(
+            berlekamp_massey(&powers),
+            Some(LinearRecurrence::new(vec![2], vec![1 << 30]))
+        );
+        let alternating = LinearRecurrence::new(vec![-1, 3, -2], vec![5, -4, 7]);
+        let late_alternating: Vec<i64> = alternating.terms().skip(40).take(12).collect();
+        assert_eq!(
+            berlekamp_massey(&late_alternating)
+                .map(|recurrence| recurrence.coefficients().to_vec()),
+            Some(vec![-1, 3, -2])
+        );
+
+        // Irregular sequences need fractions, whatever the size of the terms.
+        assert_eq!(
+            berlekamp_massey(&[1, 3, 7, 2, 9, 100, -4, 55, 13, 8, 1, 2]),
+            None
+        );
+        assert_eq!(berlekamp_massey(&[i64::MAX, i64::MIN, i64::MAX, 1]), None);
+        // Coefficients as large as the terms.
+        assert_eq!(
+            berlekamp_massey(&[1, 1 << 40]),
+            Some(LinearRecurrence::new(vec![1 << 40], vec![1]))
+        );
+
+        // a(n) = a(n - 1) / 2 needs a fractional coefficient.
+        assert_eq!(berlekamp_massey(&[8, 4, 2, 1]), None);
+        assert_eq!(
+            berlekamp_massey(&[0, 0, 0]),
+            Some(LinearRecurrence::new(vec![0], vec![0]))
+        );
+    }
+
+    #[test]
+    fn prime_field_arithmetic() {
+        let minus_one = to_residue(-1);
+        assert_eq!(mul_mod(minus_one, minus_one), 1);
+        assert_eq!(from_residue(minus_one), Some(-1));
+        assert_eq!(from_residue(to_residue(i64::MIN)), Some(i64::MIN));
+        assert_eq!(from_residue(1 << 100), None);
+
+        let big = to_residue(i64::MAX);
+        assert_eq!(mul_mod(big, inverse_mod(big)), 1);
+        // (2^63 - 1)^2 = 2^126 - 2^64 + 1 is below the prime.
+        assert_eq!(mul_mod(big, big), (1 << 126) - (1 << 64) + 1);
+        // 2^64 * 2^64 = 2^128 = 2.
+        assert_eq!(mul_mod(1 << 64, 1 << 64), 2);
+    }
+}
@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Exact fraction, always stored in lowest terms with a positive denominator so that equal values
+/// have equal representations.
+///
+/// Arithmetic panics if the reduced result doesn't fit in an i64 numerator / denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    pub fn new(numerator: i64, denominator: i64) -> Self {
+        assert!(denominator != 0, "denominator must not be zero");
+        Self::reduced(numerator as i128, denominator as i128)
+    }
+
+    pub fn from_integer(value: i64) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    /// None if the result doesn't fit.
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        // i64 * i64 always fits in an i128, only the sum can overflow.
+        let numerator = (self.numerator as i128 * other.denominator as i128)
+            .checked_add(other.numerator as i128 * self.denominator as i128)?;
+        Rational::checked_reduced(
+            numerator,
+            self.denominator as i128 * other.denominator as i128,
+        )
+    }
+
+    /// None if the result doesn't fit.
+    pub fn checked_sub(self, other: Rational) -> Option<Rational> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    /// None if the result doesn't fit (only for a numerator of i64::MIN).
+    pub fn checked_neg(self) -> Option<Rational> {
+        Rational::checked_reduced(-(self.numerator as i128), self.denominator as i128)
+    }
+
+    /// None if the result doesn't fit.
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        Rational::checked_reduced(
+            self.numerator as i128 * other.numerator as i128,
+            self.denominator as i128 * other.denominator as i128,
+        )
+    }
+
+    /// None if other is zero or the result doesn't fit.
+    pub fn checked_div(self, other: Rational) -> Option<Rational> {
+        if other.numerator == 0 {
+            return None;
+        }
+        Rational::checked_reduced(
+            self.numerator as i128 * other.denominator as i128,
+            self.denominator as i128 * other.numerator as i128,
+        )
+    }
+
+    fn reduced(numerator: i128, denominator: i128) -> Self {
+        Self::checked_reduced(numerator, denominator).expect("rational overflowed i64")
+    }
+
+    fn checked_reduced(mut numerator: i128, mut denominator: i128) -> Option<Self> {
+        if denominator < 0 {
+            numerator = -numerator;
+            denominator = -denominator;
+        }
+
+        let divisor = gcd(numerator.abs(), denominator);
+        Some(Self {
+            numerator: i64::try_from(numerator / divisor).ok()?,
+            denominator: i64::try_from(denominator / divisor).ok()?,
+        })
+    }
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        let remainder = a % b;
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self::from_integer(value)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        self.checked_add(other).expect("rational overflowed i64")
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, other: Rational) -> Rational {
+        self + -other
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        self.checked_neg().expect("rational overflowed i64")
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, other: Rational) -> Rational {
+        self.checked_mul(other).expect("rational overflowed i64")
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    /// Panics if other is zero.
+    fn div(self, other: Rational) -> Rational {
+        assert!(other.numerator != 0, "division by zero");
+        self.checked_div(other).expect("rational overflowed i64")
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Denominators are positive, so cross multiplying keeps the order.
+        (self.numerator as i128 * other.denominator as i128)
+            .cmp(&(other.numerator as i128 * self.denominator as i128))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stays_in_lowest_terms() {
+        assert_eq!(Rational::new(2, 4), Rational::new(1, 2));
+        assert_eq!(Rational::new(3, -6), Rational::new(-1, 2));
+        assert_eq!(Rational::new(0, 5), Rational::from(0));
+        assert_eq!(Rational::new(6, 3).to_string(), "2");
+        assert_eq!(Rational::new(-6, 4).to_string(), "-3/2");
+    }
+
+    #[test]
+    fn adds_and_multiplies_exactly() {
+        // 0.1 + 0.2 == 0.3, unlike with floats.
+        let tenth = Rational::new(1, 10);
+        assert_eq!(tenth + Rational::new(2, 10), Rational::new(3, 10));
+        assert_eq!(
+            Rational::new(2, 3) * Rational::new(9, 4),
+            Rational::new(3, 2)
+        );
+        assert_eq!(
+            Rational::new(1, 2) - Rational::new(3, 4),
+            Rational::new(-1, 4)
+        );
+        assert_eq!(
+            Rational::new(1, 2) / Rational::new(-3, 4),
+            Rational::new(-2, 3)
+        );
+    }
+
+    #[test]
+    fn checked_arithmetic_reports_overflow() {
+        let big = Rational::new(i64::MAX, 2);
+        assert_eq!(
+            Rational::from(i64::MAX).checked_add(Rational::from(1)),
+            None
+        );
+        assert_eq!(big.checked_mul(Rational::from(3)), None);
+        assert_eq!(Rational::from(i64::MIN).checked_neg(), None);
+        assert_eq!(Rational::from(1).checked_div(Rational::from(0)), None);
+        assert_eq!(
+            Rational::new(1, i64::MAX).checked_sub(Rational::new(1, i64::MAX - 1)),
+            None
+        );
+        assert_eq!(
+            big.checked_sub(Rational::new(1, 2)),
+            Some(Rational::from(i64::MAX / 2))
+        );
+    }
+
+    #[test]
+    fn orders_by_value() {
+        assert!(Rational::new(1, 3) < Rational::new(1, 2));
+        assert!(Rational::new(-1, 2) < Rational::new(1, 3));
+        assert!(Rational::new(7, 2) > Rational::from(3));
+    }
+}
@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+
+/*
+Problem:
+Check whether one sequence is a rotation of another, e.g. "erbottlewat" of "waterbottle".
+
+Solution:
+b is a rotation of a exactly when they have the same length and b appears in a + a. Searching for
+it with Knuth-Morris-Pratt keeps it linear, and indexing a modulo its length avoids building a + a.
+
+Time complexity: O(N)
+Space complexity: O(N) for the KMP failure table.
+*/
+pub fn is_rotation<T: Eq>(a: &[T], b: &[T]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    if b.is_empty() {
+        return true;
+    }
+
+    // failure[i] is the length of the longest proper prefix of b[..=i] that's also its suffix.
+    let mut failure = vec![0; b.len()];
+    let mut matched = 0;
+    for i in 1..b.len() {
+        while matched > 0 && b[i] != b[matched] {
+            matched = failure[matched - 1];
+        }
+        if b[i] == b[matched] {
+            matched += 1;
+        }
+        failure[i] = matched;
+    }
+
+    // The last character of a + a can't start a match, so it's skipped.
+    let mut matched = 0;
+    for i in 0..2 * a.len() - 1 {
+        let element = &a[i % a.len()];
+        while matched > 0 && *element != b[matched] {
+            matched = failure[matched - 1];
+        }
+        if *element == b[matched] {
+            matched += 1;
+        }
+        if matched == b.len() {
+            return true;
+        }
+    }
+    false
+}
+
+pub fn is_rotation_str(a: &str, b: &str) -> bool {
+    // A valid string can't start in the middle of a character, so comparing bytes is enough.
+    is_rotation(a.as_bytes(), b.as_bytes())
+}
+
+/*
+Lexicographically minimal rotation (Booth's algorithm):
+Runs KMP over list + list while keeping k, the start of the smallest rotation seen so far. The
+failure table is for the rotation starting at k, and whenever a mismatch shows the current
+position would start a smaller rotation, k moves there. Every position is matched or discarded a
+constant number of times overall.
+
+Time complexity: O(N)
+Space complexity: O(N)
+*/
+/// Index where the lexicographically smallest rotation starts, the first one if it's repeated.
+/// 0 for an empty list.
+pub fn minimal_rotation<T: Ord>(list: &[T]) -> usize {
+    let n = list.len();
+    if n == 0 {
+        return 0;
+    }
+
+    let at = |index: usize| &list[index % n];
+    let mut failure: Vec<isize> = vec![-1; 2 * n];
+    let mut k = 0;
+    for j in 1..2 * n {
+        let element = at(j);
+        let mut i = failure[j - k - 1];
+        while i != -1 && element != at(k + i as usize + 1) {
+            if element < at(k + i as usize + 1) {
+                k = j - i as usize - 1;
+            }
+            i = failure[i as usize];
+        }
+
+        // i is -1 here unless the loop stopped on a match.
+        if i == -1 && element != at(k) {
+            if element < at(k) {
+                k = j;
+            }
+            failure[j - k] = -1;
+        } else {
+            failure[j - k] = i + 1;
+        }
+    }
+    k % n
+}
+
+/// The lexicographically smallest rotation, equal for all rotations of the same sequence so it
+/// can be used to deduplicate necklaces.
+pub fn canonical_rotation<T: Ord + Clone>(list: &[T]) -> Vec<T> {
+    let start = minimal_rotation(list);
+    list[start..]
+        .iter()
+        .chain(&list[..start])
+        .cloned()
+        .collect()
+}
+
+/// canonical_rotation by characters.
+pub fn canonical_rotation_str(string: &str) -> String {
+    let chars: Vec<char> = string.chars().collect();
+    canonical_rotation(&chars).into_iter().collect()
+}
+
+/// Orders sequences by their canonical rotations, so necklace-equivalent sequences compare
+/// equal.
+pub fn cmp_rotations<T: Ord + Clone>(a: &[T], b: &[T]) -> Ordering {
+    canonical_rotation(a).cmp(&canonical_rotation(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn rotations(list: &[u8]) -> Vec<Vec<u8>> {
+        (0..list.len())
+            .map(|start| [&list[start..], &list[..start]].concat())
+            .collect()
+    }
+
+    /// Every sequence of 0s, 1s and 2s up to length 6.
+    fn small_sequences() -> Vec<Vec<u8>> {
+        let mut sequences = vec![vec![]];
+        let mut previous = vec![vec![]];
+        for _ in 0..6 {
+            previous = previous
+                .iter()
+                .flat_map(|sequence: &Vec<u8>| {
+                    (0..3).map(move |digit| [&sequence[..], &[digit]].concat())
+                })
+                .collect();
+            sequences.extend(previous.iter().cloned());
+        }
+        sequences
+    }
+
+    #[test]
+    fn detects_rotations() {
+        assert!(is_rotation_str("waterbottle", "erbottlewat"));
+        assert!(is_rotation_str("aab", "aba"));
+        assert!(is_rotation_str("", ""));
+        assert!(!is_rotation_str("waterbottle", "erbottlewta"));
+        assert!(!is_rotation_str("ab", "abab"));
+        assert!(is_rotation_str("héllo", "llohé"));
+
+        let length_four: Vec<Vec<u8>> = small_sequences()
+            .into_iter()
+            .filter(|sequence| sequence.len() == 4)
+            .collect();
+        for a in &length_four {
+            let a_rotations = rotations(a);
+            for b in &length_four {
+                assert_eq!(is_rotation(a, b), a_rotations.contains(b));
+            }
+        }
+    }
+
+    #[test]
+    fn minimal_rotation_matches_brute_force() {
+        for sequence in small_sequences() {
+            let all = rotations(&sequence);
+            let smallest = all.iter().min().cloned().unwrap_or_default();
+            let first = all.iter().position(|rotation| *rotation == smallest);
+
+            assert_eq!(minimal_rotation(&sequence), first.unwrap_or(0));
+            assert_eq!(canonical_rotation(&sequence), smallest);
+        }
+    }
+
+    #[test]
+    fn canonical_forms_dedup_necklaces() {
+        assert_eq!(canonical_rotation_str("bca"), "abc");
+        assert_eq!(canonical_rotation_str("héhe"), "ehéh");
+        assert_eq!(minimal_rotation(&[2, 1, 2, 1]), 1);
+        assert_eq!(cmp_rotations(&[3, 1, 2], &[2, 3, 1]), Ordering::Equal);
+        assert_eq!(cmp_rotations(&[1, 3, 2], &[1, 2, 3]), Ordering::Greater);
+
+        // There are 14 binary necklaces of length 6.
+        let necklaces: HashSet<Vec<u8>> = small_sequences()
+            .into_iter()
+            .filter(|sequence| sequence.len() == 6 && sequence.iter().all(|&digit| digit < 2))
+            .map(|sequence| canonical_rotation(&sequence))
+            .collect();
+        assert_eq!(necklaces.len(), 14);
+    }
+}
@@ -0,0 +1,30 @@
+use ego_binary_tree::{BinaryNodeMut, BinaryTree};
+use std::thread;
+
+/// Appends values under node as a chain of right children. Recursive, since ego_binary_tree only
+/// hands out children that borrow their parent, so there's no way to walk down in a loop.
+pub(crate) fn append_right_chain(node: &mut BinaryNodeMut<u32>, values: &[u32]) {
+    if let Some((first, rest)) = values.split_first() {
+        let mut child = node.set_right(*first);
+        append_right_chain(&mut child, rest);
+    }
+}
+
+/// 0, 1, ..., len - 1 as a chain of right children.
+pub(crate) fn right_chain(len: u32) -> BinaryTree<u32> {
+    let values: Vec<u32> = (0..len).collect();
+    let mut tree = BinaryTree::new(values[0]);
+    append_right_chain(&mut tree.root_mut(), &values[1..]);
+    tree
+}
+
+/// right_chain for chains too deep for the default stack. Only the building happens on a thread
+/// with room for the recursion, the returned tree can be used anywhere.
+pub(crate) fn deep_right_chain(len: u32) -> BinaryTree<u32> {
+    thread::Builder::new()
+        .stack_size(len as usize * 1024)
+        .spawn(move || right_chain(len))
+        .expect("spawned builder thread")
+        .join()
+        .expect("builder thread finished")
+}